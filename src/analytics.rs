@@ -0,0 +1,414 @@
+use crate::betting::{BettingRamp, SimulationReport};
+use crate::counting::CountingSystem;
+use crate::game_settings::GameSettings;
+use crate::money::Money;
+use crate::shoe::Shoe;
+
+/// Player advantage gained for each point of Hi-Lo true count
+pub const EDGE_PER_TRUE_COUNT: f64 = 0.005;
+
+/// Variance of one hand in squared initial bets, allowing for doubles and splits
+pub const HAND_VARIANCE: f64 = 1.33;
+
+/// Largest true count (positive or negative) tracked by the frequency estimate
+const MAX_TRUE_COUNT: i32 = 20;
+
+/// How often each (truncated) true count comes up at the start of a round
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrueCountFrequencies {
+    /// (true count, probability) pairs in ascending true count order, summing to 1
+    pub frequencies: Vec<(i32, f64)>,
+}
+
+impl TrueCountFrequencies {
+    /// Estimates the true count distribution for a shoe dealt to `penetration`
+    ///
+    /// Rounds are assumed to start evenly through the dealt part of the shoe. At
+    /// each point the running count is close to normally distributed, with a
+    /// variance that grows as the shoe is dealt, so the true count spreads out
+    /// the deeper the cut card is placed.
+    ///
+    /// # Returns
+    /// `Err` for unbalanced systems, which do not use a true count
+    pub fn estimate(system: CountingSystem, deck_count: usize, penetration: f64) -> Result<Self, String> {
+        if !system.is_balanced() {
+            return Err(format!("{} is unbalanced and has no true count", system));
+        }
+        let tag_variance = tag_variance(system);
+        let total = (deck_count * 52) as f64;
+        let dealt = (total * penetration).floor() as usize;
+
+        let mut probabilities = vec![0f64; (2 * MAX_TRUE_COUNT + 1) as usize];
+        for cards in 0..=dealt {
+            let cards = cards as f64;
+            let remaining = total - cards;
+            if remaining < 1.0 {
+                break;
+            }
+            let variance = tag_variance * 52f64.powi(2) * cards / ((total - 1.0) * remaining);
+            for (index, probability) in probabilities.iter_mut().enumerate() {
+                let count = index as i32 - MAX_TRUE_COUNT;
+                *probability += truncated_count_probability(count, variance.sqrt());
+            }
+        }
+
+        let sum: f64 = probabilities.iter().sum();
+        Ok(Self {
+            frequencies: probabilities.into_iter()
+                .enumerate()
+                .map(|(index, probability)| (index as i32 - MAX_TRUE_COUNT, probability / sum))
+                .collect(),
+        })
+    }
+
+    /// Probability of the given true count
+    pub fn probability(&self, true_count: i32) -> f64 {
+        self.frequencies.iter()
+            .find(|(count, _)| *count == true_count)
+            .map_or(0f64, |(_, probability)| *probability)
+    }
+}
+
+/// Expected result and standard deviation of one round played, in betting units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundStats {
+    pub ev: f64,
+    pub std_dev: f64,
+}
+
+impl RoundStats {
+    pub fn new(ev: f64, std_dev: f64) -> Self {
+        Self { ev, std_dev }
+    }
+
+    /// Uses the per-round results of a simulation
+    pub fn from_report(report: &SimulationReport) -> Self {
+        Self::new(report.ev_per_round, report.std_dev)
+    }
+
+    /// Derives the stats of a betting ramp from the player's edge at each true count
+    ///
+    /// # Arguments
+    /// * `player_edge` - Edge off the top of the shoe, e.g. from `Rules::player_edge`
+    /// * `edge_per_true_count` - Edge gained per point of true count
+    /// * `ramp` - Bets to place at each count; wonged out counts are not played
+    /// * `frequencies` - How often each true count comes up
+    pub fn for_ramp(
+        player_edge: f64,
+        edge_per_true_count: f64,
+        ramp: &BettingRamp,
+        frequencies: &TrueCountFrequencies,
+    ) -> Self {
+        let (mut played, mut ev, mut second_moment) = (0f64, 0f64, 0f64);
+        for (count, probability) in frequencies.frequencies.iter() {
+            // Ignores the gap between the wong in and wong out counts, where play
+            // depends on whether the player was already seated
+            let plays = ramp.wong_in.is_none_or(|wong_in| *count >= wong_in)
+                && ramp.wong_out.is_none_or(|wong_out| *count > wong_out);
+            if !plays {
+                continue;
+            }
+            let units = ramp.units_for(*count) as f64;
+            let edge = player_edge + edge_per_true_count * *count as f64;
+            played += probability;
+            ev += probability * units * edge;
+            second_moment += probability * units.powi(2) * (HAND_VARIANCE + edge.powi(2));
+        }
+        if played == 0f64 {
+            return Self::new(0f64, 0f64);
+        }
+        let ev = ev / played;
+        Self::new(ev, (second_moment / played - ev.powi(2)).sqrt())
+    }
+
+    /// Derives the stats of a betting ramp analytically from the table's rules,
+    /// deck count, penetration and counting system
+    ///
+    /// # Returns
+    /// `Err` if the table's counting system has no true count or there is no
+    /// house edge estimate for its game
+    pub fn estimate(settings: &GameSettings, ramp: &BettingRamp) -> Result<Self, String> {
+        let deck_count = settings.deck_count as usize;
+        let player_edge = settings.rules.player_edge(deck_count)
+            .ok_or_else(|| format!("There is no house edge estimate for {}", settings.rules.variant))?;
+        let frequencies = TrueCountFrequencies::estimate(settings.counting_system, deck_count, settings.penetration)?;
+        Ok(Self::for_ramp(
+            player_edge,
+            edge_per_true_count(settings.counting_system),
+            ramp,
+            &frequencies,
+        ))
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.std_dev.powi(2)
+    }
+
+    /// Chance of ever losing a bankroll of `bankroll` units when playing forever
+    pub fn risk_of_ruin(&self, bankroll: f64) -> f64 {
+        if self.ev <= 0f64 {
+            return 1f64;
+        }
+        if self.std_dev == 0f64 {
+            return 0f64;
+        }
+        (-2f64 * self.ev * bankroll / self.variance()).exp().min(1f64)
+    }
+
+    /// Units needed to keep the risk of ruin down to `risk`, `None` without an edge
+    pub fn bankroll_for_risk(&self, risk: f64) -> Option<f64> {
+        if self.ev <= 0f64 || !(risk > 0f64 && risk < 1f64) {
+            return None;
+        }
+        Some(-self.variance() * risk.ln() / (2f64 * self.ev))
+    }
+
+    /// Chance of losing a trip bankroll of `bankroll` units within `hours` of play
+    ///
+    /// # Arguments
+    /// * `rounds_per_hour` - Rounds actually played per hour
+    pub fn trip_ruin(&self, bankroll: f64, hours: f64, rounds_per_hour: f64) -> f64 {
+        let rounds = hours * rounds_per_hour;
+        if rounds <= 0f64 {
+            return 0f64;
+        }
+        if self.std_dev == 0f64 {
+            return if self.ev * rounds <= -bankroll { 1f64 } else { 0f64 };
+        }
+        // Chance a random walk with this drift touches -bankroll within the trip
+        let spread = self.std_dev * rounds.sqrt();
+        let drift = self.ev * rounds;
+        let reflection = (-2f64 * self.ev * bankroll / self.variance()).exp();
+        let ruin = normal_cdf((-bankroll - drift) / spread) + reflection * normal_cdf((-bankroll + drift) / spread);
+        ruin.min(1f64)
+    }
+
+    /// Rounds needed for the expected win to equal one standard deviation
+    pub fn n0(&self) -> f64 {
+        if self.ev == 0f64 {
+            return f64::INFINITY;
+        }
+        self.variance() / self.ev.powi(2)
+    }
+
+    /// 1,000 × EV / SD, the usual yardstick for comparing games and ramps
+    pub fn desirability_index(&self) -> f64 {
+        if self.std_dev == 0f64 {
+            return 0f64;
+        }
+        1_000f64 * self.ev / self.std_dev
+    }
+
+    /// Expected win per 100 rounds, in dollars, for a $10,000 bankroll bet at
+    /// optimal (Kelly) size. Zero for games without an edge.
+    pub fn score(&self) -> f64 {
+        if self.ev <= 0f64 {
+            return 0f64;
+        }
+        self.desirability_index().powi(2)
+    }
+}
+
+/// Kelly-criterion bet sizes for a bankroll at the current count
+///
+/// Full Kelly bets the fraction of the bankroll equal to advantage / variance,
+/// which grows the bankroll fastest but swings hard; half and quarter Kelly
+/// give up a little growth for much lower risk. Every amount is zero when the
+/// player has no advantage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KellyBet {
+    /// Estimated player advantage at this count, as a fraction of the bet
+    pub advantage: f64,
+    /// Estimated variance of a hand at this count, in squared bets
+    pub variance: f64,
+    pub full: Money,
+    pub half: Money,
+    pub quarter: Money,
+}
+
+impl KellyBet {
+    /// Kelly bets for `bankroll` at the given advantage and variance
+    pub fn new(bankroll: Money, advantage: f64, variance: f64) -> Self {
+        let fraction = if advantage > 0f64 && variance > 0f64 { advantage / variance } else { 0f64 };
+        let bet = |share: f64| Money::from_cents((bankroll.cents().max(0) as f64 * fraction * share).floor() as i64);
+        Self {
+            advantage,
+            variance,
+            full: bet(1f64),
+            half: bet(0.5),
+            quarter: bet(0.25),
+        }
+    }
+
+    /// Kelly bets for `bankroll` at `true_count` under the table's rules and counting system
+    ///
+    /// # Returns
+    /// `None` if there is no house edge estimate for the table's game
+    pub fn at_true_count(bankroll: Money, true_count: f64, settings: &GameSettings) -> Option<Self> {
+        let advantage = settings.rules.player_edge(settings.deck_count as usize)?
+            + edge_per_true_count(settings.counting_system) * true_count;
+        Some(Self::new(bankroll, advantage, HAND_VARIANCE + advantage.powi(2)))
+    }
+}
+
+/// Edge gained per point of true count under `system`
+///
+/// Scaled from the Hi-Lo figure by the spread of the system's tags, since a
+/// count that uses larger tags moves further for the same cards.
+pub fn edge_per_true_count(system: CountingSystem) -> f64 {
+    EDGE_PER_TRUE_COUNT * (tag_variance(CountingSystem::HiLo) / tag_variance(system)).sqrt()
+}
+
+/// Variance of the tag of a single card drawn from a full deck
+fn tag_variance(system: CountingSystem) -> f64 {
+    let tags: Vec<f64> = Shoe::new(1).cards.iter().map(|card| system.tag(card) as f64).collect();
+    let mean = tags.iter().sum::<f64>() / tags.len() as f64;
+    tags.iter().map(|tag| (tag - mean).powi(2)).sum::<f64>() / tags.len() as f64
+}
+
+/// Chance that a normal true count with the given spread truncates to `count`
+fn truncated_count_probability(count: i32, std_dev: f64) -> f64 {
+    let (low, high) = match count {
+        0 => (-1f64, 1f64),
+        c if c > 0 => (c as f64, c as f64 + 1f64),
+        c => (c as f64 - 1f64, c as f64),
+    };
+    if std_dev == 0f64 {
+        return if count == 0 { 1f64 } else { 0f64 };
+    }
+    normal_cdf(high / std_dev) - normal_cdf(low / std_dev)
+}
+
+/// Standard normal cumulative distribution function
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1f64 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Error function, accurate to about 1e-7 (Abramowitz and Stegun 7.1.26)
+fn erf(x: f64) -> f64 {
+    let t = 1f64 / (1f64 + 0.3275911 * x.abs());
+    let polynomial = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let value = 1f64 - polynomial * (-x * x).exp();
+    if x < 0f64 { -value } else { value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn test_risk_of_ruin() {
+        let stats = RoundStats::new(0.02, 1.2);
+        // exp(-2 × 0.02 × 100 / 1.44)
+        assert!(close(stats.risk_of_ruin(100.0), 0.0622, 0.0001));
+        let bankroll = stats.bankroll_for_risk(0.05).unwrap();
+        assert!(close(stats.risk_of_ruin(bankroll), 0.05, 1e-9));
+
+        let losing = RoundStats::new(-0.005, 1.15);
+        assert_eq!(losing.risk_of_ruin(1_000.0), 1.0);
+        assert_eq!(losing.bankroll_for_risk(0.05), None);
+    }
+
+    #[test]
+    fn test_n0_score_and_desirability_index() {
+        let stats = RoundStats::new(0.02, 2.0);
+        assert!(close(stats.n0(), 10_000.0, 1e-6));
+        assert!(close(stats.desirability_index(), 10.0, 1e-9));
+        assert!(close(stats.score(), 100.0, 1e-9));
+        // SCORE is DI squared, or 1,000,000 / N0
+        assert!(close(stats.score(), 1_000_000.0 / stats.n0(), 1e-9));
+        assert_eq!(RoundStats::new(-0.01, 1.0).score(), 0.0);
+    }
+
+    #[test]
+    fn test_trip_ruin_grows_towards_risk_of_ruin() {
+        let stats = RoundStats::new(0.02, 1.2);
+        let short = stats.trip_ruin(50.0, 4.0, 100.0);
+        let long = stats.trip_ruin(50.0, 40.0, 100.0);
+        let forever = stats.risk_of_ruin(50.0);
+        assert!(short < long);
+        assert!(long < forever);
+        assert!(close(stats.trip_ruin(50.0, 100_000.0, 100.0), forever, 1e-6));
+        assert_eq!(stats.trip_ruin(50.0, 0.0, 100.0), 0.0);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!(close(normal_cdf(0.0), 0.5, 1e-7));
+        assert!(close(normal_cdf(1.96), 0.975, 1e-4));
+        assert!(close(normal_cdf(-1.0), 0.1587, 1e-4));
+    }
+
+    #[test]
+    fn test_true_count_frequencies() {
+        let shallow = TrueCountFrequencies::estimate(CountingSystem::HiLo, 6, 0.5).unwrap();
+        let deep = TrueCountFrequencies::estimate(CountingSystem::HiLo, 6, 0.85).unwrap();
+
+        let total: f64 = deep.frequencies.iter().map(|(_, probability)| probability).sum();
+        assert!(close(total, 1.0, 1e-9));
+        assert!(close(deep.probability(3), deep.probability(-3), 1e-9));
+        assert!(deep.probability(0) > deep.probability(1));
+        assert!(deep.probability(4) > shallow.probability(4));
+        assert!(TrueCountFrequencies::estimate(CountingSystem::KO, 6, 0.75).is_err());
+    }
+
+    #[test]
+    fn test_estimate_from_rules() {
+        let settings = GameSettings::new("Player".to_string(), 6);
+        let flat = RoundStats::estimate(&settings, &BettingRamp::flat(Money::from_dollars(10))).unwrap();
+        assert!(close(flat.ev, settings.rules.player_edge(6).unwrap(), 1e-9));
+        assert!(close(flat.std_dev, HAND_VARIANCE.sqrt(), 0.01));
+
+        let spread = RoundStats::estimate(&settings, &BettingRamp::one_to_twelve(Money::from_dollars(10))).unwrap();
+        assert!(spread.ev > 0.0);
+        assert!(spread.std_dev > flat.std_dev);
+        assert!(spread.risk_of_ruin(1_000.0) < 0.5);
+    }
+
+    #[test]
+    fn test_wonging_raises_ev_per_round_played() {
+        let settings = GameSettings::new("Player".to_string(), 6);
+        let mut ramp = BettingRamp::flat(Money::from_dollars(10));
+        let flat = RoundStats::estimate(&settings, &ramp).unwrap();
+        ramp.wong_in = Some(1);
+        ramp.wong_out = Some(0);
+        let wonged = RoundStats::estimate(&settings, &ramp).unwrap();
+
+        assert!(wonged.ev > 0.0);
+        assert!(wonged.ev > flat.ev);
+    }
+
+    #[test]
+    fn test_kelly_bet() {
+        let kelly = KellyBet::new(Money::from_dollars(10_000), 0.01, 1.25);
+        assert_eq!(kelly.full, Money::from_dollars(80));
+        assert_eq!(kelly.half, Money::from_dollars(40));
+        assert_eq!(kelly.quarter, Money::from_dollars(20));
+
+        let no_edge = KellyBet::new(Money::from_dollars(10_000), -0.004, 1.33);
+        assert_eq!(no_edge.full, Money::ZERO);
+    }
+
+    #[test]
+    fn test_kelly_bet_follows_true_count() {
+        let settings = GameSettings::new("Player".to_string(), 6);
+        let bankroll = Money::from_dollars(10_000);
+        assert_eq!(KellyBet::at_true_count(bankroll, 0.0, &settings).unwrap().full, Money::ZERO);
+
+        let low = KellyBet::at_true_count(bankroll, 2.0, &settings).unwrap();
+        let high = KellyBet::at_true_count(bankroll, 5.0, &settings).unwrap();
+        assert!(low.full.is_positive());
+        assert!(high.full > low.full);
+        assert!(close(high.advantage, settings.rules.player_edge(6).unwrap() + 0.025, 1e-9));
+    }
+
+    #[test]
+    fn test_edge_per_true_count_scales_with_tags() {
+        assert_eq!(edge_per_true_count(CountingSystem::HiLo), EDGE_PER_TRUE_COUNT);
+        assert!(edge_per_true_count(CountingSystem::OmegaII) < EDGE_PER_TRUE_COUNT);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::card::Card;
+use crate::game::{Game, GameAction, GameState};
+use crate::game_settings::GameSettings;
+use crate::ledger::Ledger;
+use crate::money::Money;
+use crate::strategy::basic_strategy;
+
+/// Maps the true count to a bet size in units, with optional wonging thresholds
+#[derive(Debug, Clone, PartialEq)]
+pub struct BettingRamp {
+    /// Value of one betting unit
+    pub unit: Money,
+    /// (true count, units) pairs in ascending true count order. The bet is the
+    /// units of the highest step at or below the current true count; counts
+    /// below the first step bet the first step's units.
+    pub steps: Vec<(i32, u32)>,
+    /// Back-count and only join the game once the true count reaches this value
+    pub wong_in: Option<i32>,
+    /// Leave the game when the true count drops to this value or below
+    pub wong_out: Option<i32>,
+}
+
+impl BettingRamp {
+    /// Creates a ramp that plays every round
+    pub fn new(unit: Money, steps: Vec<(i32, u32)>) -> Self {
+        Self {
+            unit,
+            steps,
+            wong_in: None,
+            wong_out: None,
+        }
+    }
+
+    /// A common 1-12 spread for a six-deck Hi-Lo game
+    pub fn one_to_twelve(unit: Money) -> Self {
+        Self::new(unit, vec![(1, 1), (2, 2), (3, 4), (4, 8), (5, 12)])
+    }
+
+    /// Flat betting of a single unit
+    pub fn flat(unit: Money) -> Self {
+        Self::new(unit, vec![(0, 1)])
+    }
+
+    /// Units to bet at the given true count
+    pub fn units_for(&self, true_count: i32) -> u32 {
+        self.steps.iter()
+            .rev()
+            .find(|(count, _)| true_count >= *count)
+            .or(self.steps.first())
+            .map_or(0, |(_, units)| *units)
+    }
+
+    /// Bet to place at the given true count
+    pub fn bet_for(&self, true_count: i32) -> Money {
+        self.unit * self.units_for(true_count) as i64
+    }
+
+    /// Ratio of the largest to the smallest bet in the ramp
+    pub fn spread(&self) -> f64 {
+        let units = self.steps.iter().map(|(_, units)| *units);
+        let min = units.clone().min().unwrap_or(0);
+        let max = units.max().unwrap_or(0);
+        if min == 0 {
+            return 0f64;
+        }
+        max as f64 / min as f64
+    }
+
+    /// Decides whether a player who is currently `seated` plays at this true count
+    ///
+    /// Without a wong in threshold a player who has left waits for the next shoe.
+    pub fn plays_at(&self, seated: bool, true_count: i32) -> bool {
+        if seated {
+            self.wong_out.is_none_or(|out| true_count > out)
+        } else {
+            self.wong_in.is_some_and(|threshold| true_count >= threshold)
+        }
+    }
+
+    /// Checks the ramp is well formed and every bet fits the table limits
+    pub fn validate(&self, settings: &GameSettings) -> Result<(), String> {
+        if self.steps.is_empty() {
+            return Err("Betting ramp needs at least one step".to_string());
+        }
+        if self.steps.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err("Betting ramp steps must be in ascending true count order".to_string());
+        }
+        if let (Some(wong_in), Some(wong_out)) = (self.wong_in, self.wong_out) {
+            if wong_out >= wong_in {
+                return Err("Wong out count must be below the wong in count".to_string());
+            }
+        }
+        for (count, units) in self.steps.iter() {
+            settings.check_bet(self.unit * *units as i64)
+                .map_err(|e| format!("Bet at true count {}: {}", count, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Results of playing a betting ramp over many rounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub rounds_played: usize,
+    /// Rounds dealt while the player was wonged out
+    pub rounds_sat_out: usize,
+    /// Everything wagered, including doubles and splits
+    pub total_wagered: Money,
+    pub net: Money,
+    /// Net result as a fraction of the total wagered
+    pub advantage: f64,
+    /// Average result per round played, in units
+    pub ev_per_round: f64,
+    /// Variance of the result per round played, in units squared
+    pub variance: f64,
+    /// Standard deviation of the result per round played, in units
+    pub std_dev: f64,
+    /// Average initial bet, in units
+    pub average_bet: f64,
+    pub smallest_bet: Money,
+    pub largest_bet: Money,
+    /// Ratio of the largest to the smallest initial bet actually placed
+    pub bet_spread: f64,
+}
+
+/// Plays `rounds` rounds of basic strategy, sizing each bet with `ramp` from the
+/// true count at the start of the round
+///
+/// The player is given an effectively unlimited bankroll so the results are not
+/// cut short. While wonged out the round is still dealt to a stand-in betting
+/// the table minimum, so the shoe is used up realistically, but it is left out
+/// of the report.
+///
+/// # Returns
+/// `Err` if the settings or the ramp are invalid
+pub fn simulate(settings: GameSettings, ramp: &BettingRamp, rounds: usize) -> Result<SimulationReport, String> {
+    settings.validate()?;
+    ramp.validate(&settings)?;
+
+    let mut game = Game::new(settings);
+    game.shuffle_shoe();
+    game.player.bank_roll = Money::from_dollars(1_000_000_000_000);
+
+    let unit = ramp.unit.as_dollars();
+    let mut seated = ramp.wong_in.is_none();
+    let mut results: Vec<f64> = Vec::with_capacity(rounds);
+    let mut bets: Vec<Money> = Vec::with_capacity(rounds);
+    let mut total_wagered = Money::ZERO;
+    let mut net = Money::ZERO;
+    let mut rounds_sat_out = 0;
+
+    for _ in 0..rounds {
+        let true_count = game.true_count().trunc() as i32;
+        seated = ramp.plays_at(seated, true_count);
+        let bet = if seated { ramp.bet_for(true_count) } else { game.settings.min_bet };
+
+        let starting_bankroll = game.player.bank_roll;
+        game.player.ledger = Ledger::new(starting_bankroll);
+        game.accept_user_bet(bet)?;
+        play_round(&mut game)?;
+
+        if seated {
+            let result = game.player.bank_roll - starting_bankroll;
+            total_wagered += game.player.ledger.summary().total_wagered;
+            net += result;
+            results.push(result.as_dollars() / unit);
+            bets.push(bet);
+        } else {
+            rounds_sat_out += 1;
+        }
+
+        game.next_round();
+        if game.shoe.discarded.is_empty() && ramp.wong_in.is_none() {
+            // A player who left the last shoe sits back down for the new one
+            seated = true;
+        }
+    }
+
+    let rounds_played = results.len();
+    let ev_per_round = mean(&results);
+    let variance = if rounds_played > 1 {
+        results.iter().map(|r| (r - ev_per_round).powi(2)).sum::<f64>() / (rounds_played - 1) as f64
+    } else {
+        0f64
+    };
+    let smallest_bet = bets.iter().min().copied().unwrap_or_default();
+    let largest_bet = bets.iter().max().copied().unwrap_or_default();
+
+    Ok(SimulationReport {
+        rounds_played,
+        rounds_sat_out,
+        total_wagered,
+        net,
+        advantage: if total_wagered.is_zero() { 0f64 } else { net.as_dollars() / total_wagered.as_dollars() },
+        ev_per_round,
+        variance,
+        std_dev: variance.sqrt(),
+        average_bet: mean(&bets.iter().map(|bet| bet.as_dollars() / unit).collect::<Vec<f64>>()),
+        smallest_bet,
+        largest_bet,
+        bet_spread: if smallest_bet.is_zero() { 0f64 } else { largest_bet.as_dollars() / smallest_bet.as_dollars() },
+    })
+}
+
+/// Deals and plays out one round with basic strategy
+fn play_round(game: &mut Game) -> Result<(), String> {
+    game.deal_initial_cards();
+    while let GameState::PlayerTurn { active_hand_index, .. } = *game.get_state() {
+        let hand = &game.player.hands[active_hand_index];
+        let can_double = game.check_action(GameAction::Double, active_hand_index).is_ok();
+        let can_split = game.check_action(GameAction::Split, active_hand_index).is_ok();
+        let can_surrender = game.check_action(GameAction::Surrender, active_hand_index).is_ok();
+        let mut action = basic_strategy(hand, &game.dealer.hands[0].cards[0], can_double, can_split, can_surrender);
+        if game.check_action(action, active_hand_index).is_err() {
+            // Split aces that may not be hit, or a Spanish 21 doubled hand kept in play for rescue
+            action = GameAction::Stand;
+        }
+        if game.check_action(action, active_hand_index).is_err() {
+            // A Pontoon hand below 15 must twist
+            action = GameAction::Hit;
+        }
+        let before = (active_hand_index, player_cards(game));
+        game.process_player_action(action, active_hand_index)?;
+        if let GameState::PlayerTurn { active_hand_index: after, .. } = *game.get_state() {
+            // Hitting or doubling from an empty shoe leaves the turn where it was
+            if (after, player_cards(game)) == before {
+                return Err(format!("{} left the hand unchanged, the shoe may be out of cards", action));
+            }
+        }
+    }
+    while let GameState::DealerTurn { .. } = game.get_state() {
+        game.next_dealer_turn();
+    }
+    Ok(())
+}
+
+fn player_cards(game: &Game) -> Vec<Vec<Card>> {
+    game.player.hands.iter().map(|hand| hand.cards.clone()).collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0f64;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::scenario::Scenario;
+
+    fn settings() -> GameSettings {
+        GameSettings::new("Simulator".to_string(), 6)
+    }
+
+    #[test]
+    fn test_units_for_true_count() {
+        let ramp = BettingRamp::one_to_twelve(Money::from_dollars(10));
+        assert_eq!(ramp.units_for(-3), 1);
+        assert_eq!(ramp.units_for(1), 1);
+        assert_eq!(ramp.units_for(3), 4);
+        assert_eq!(ramp.units_for(9), 12);
+        assert_eq!(ramp.bet_for(4), Money::from_dollars(80));
+        assert_eq!(ramp.spread(), 12f64);
+    }
+
+    #[test]
+    fn test_wonging_thresholds() {
+        let mut ramp = BettingRamp::flat(Money::from_dollars(10));
+        ramp.wong_in = Some(2);
+        ramp.wong_out = Some(-1);
+
+        assert!(!ramp.plays_at(false, 1));
+        assert!(ramp.plays_at(false, 2));
+        assert!(ramp.plays_at(true, 0));
+        assert!(!ramp.plays_at(true, -1));
+
+        ramp.wong_in = None;
+        assert!(!ramp.plays_at(false, 5));
+    }
+
+    #[test]
+    fn test_validate_ramp() {
+        let mut ramp = BettingRamp::new(Money::from_dollars(10), vec![(2, 2), (1, 1)]);
+        assert!(ramp.validate(&settings()).is_err());
+
+        ramp.steps = vec![(1, 1), (2, 1_000)];
+        assert_eq!(
+            ramp.validate(&settings()).unwrap_err(),
+            "Bet at true count 2: The table maximum is $5000.00"
+        );
+
+        ramp.steps = vec![(1, 1), (2, 4)];
+        assert!(ramp.validate(&settings()).is_ok());
+    }
+
+    #[test]
+    fn test_flat_betting_simulation() {
+        let report = simulate(settings(), &BettingRamp::flat(Money::from_dollars(10)), 2_000).unwrap();
+
+        assert_eq!(report.rounds_played, 2_000);
+        assert_eq!(report.rounds_sat_out, 0);
+        assert_eq!(report.average_bet, 1f64);
+        assert_eq!(report.bet_spread, 1f64);
+        assert!(report.total_wagered >= Money::from_dollars(20_000));
+        assert!(report.advantage.abs() < 0.2);
+        assert!(report.std_dev > 0.8 && report.std_dev < 1.6);
+    }
+
+    #[test]
+    fn test_ramp_simulation_varies_bets() {
+        let report = simulate(settings(), &BettingRamp::one_to_twelve(Money::from_dollars(10)), 3_000).unwrap();
+
+        assert_eq!(report.smallest_bet, Money::from_dollars(10));
+        assert!(report.largest_bet > report.smallest_bet);
+        assert!(report.average_bet > 1f64);
+    }
+
+    #[test]
+    fn test_wong_in_sits_out_low_counts() {
+        let mut ramp = BettingRamp::flat(Money::from_dollars(10));
+        ramp.wong_in = Some(50);
+        let report = simulate(settings(), &ramp, 200).unwrap();
+
+        assert_eq!(report.rounds_played, 0);
+        assert_eq!(report.rounds_sat_out, 200);
+        assert_eq!(report.advantage, 0f64);
+    }
+
+    #[test]
+    fn test_play_round_fails_when_the_shoe_runs_dry() {
+        let mut game = Scenario::new()
+            .player(parse_cards("2s 2d").unwrap())
+            .dealer(parse_cards("Tc 7h").unwrap())
+            .then(parse_cards("2c 2h 3s 3d").unwrap())
+            .build_game(settings())
+            .unwrap();
+        // Only the scenario's cards are left, so the hand is still on 14 when the shoe empties
+        let dealt = game.shoe.cards.len() - 8;
+        game.shoe.cards.drain(..dealt);
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+
+        assert_eq!(
+            play_round(&mut game).unwrap_err(),
+            "HIT left the hand unchanged, the shoe may be out of cards"
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(Debug, Eq, Hash, PartialEq, Clone)]
@@ -23,25 +26,66 @@ impl Rank {
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Single character used in compact notation, e.g. 'T' for Ten
+    pub fn symbol(&self) -> char {
         match self {
-            Rank::Ace => "A".to_string(),
-            Rank::Two => "2".to_string(),
-            Rank::Three => "3".to_string(),
-            Rank::Four => "4".to_string(),
-            Rank::Five => "5".to_string(),
-            Rank::Six => "6".to_string(),
-            Rank::Seven => "7".to_string(),
-            Rank::Eight => "8".to_string(),
-            Rank::Nine => "9".to_string(),
-            Rank::Ten => "10".to_string(),
-            Rank::Jack => "J".to_string(),
-            Rank::Queen => "Q".to_string(),
-            Rank::King => "K".to_string(),
+            Rank::Ace => 'A',
+            Rank::Two => '2',
+            Rank::Three => '3',
+            Rank::Four => '4',
+            Rank::Five => '5',
+            Rank::Six => '6',
+            Rank::Seven => '7',
+            Rank::Eight => '8',
+            Rank::Nine => '9',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
         }
     }
 }
 
+impl FromStr for Rank {
+    type Err = String;
+
+    /// Parses a rank symbol such as "A", "T", "10" or "k" (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        if value == "10" {
+            return Ok(Rank::Ten);
+        }
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Rank::iter()
+                .find(|rank| rank.symbol() == c.to_ascii_uppercase())
+                .ok_or_else(|| format!("Unknown rank '{}'", value)),
+            _ => Err(format!("Unknown rank '{}'", value)),
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Rank::Ace => "A",
+            Rank::Two => "2",
+            Rank::Three => "3",
+            Rank::Four => "4",
+            Rank::Five => "5",
+            Rank::Six => "6",
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+        };
+        f.write_str(symbol)
+    }
+}
+
 #[derive(Debug, Eq, Hash, PartialEq, Clone)]
 #[derive(EnumIter)]
 pub enum Suit {
@@ -52,17 +96,53 @@ pub enum Suit {
 }
 
 impl Suit {
-    pub fn to_string(&self) -> String {
+    /// Lowercase letter used in compact notation, e.g. 's' for Spades
+    pub fn letter(&self) -> char {
         match self {
-            Suit::Hearts => "❤️".to_string(),
-            Suit::Diamonds => "♦️".to_string(),
-            Suit::Clubs => "♣️".to_string(),
-            Suit::Spades => "♠️".to_string(),
+            Suit::Hearts => 'h',
+            Suit::Diamonds => 'd',
+            Suit::Clubs => 'c',
+            Suit::Spades => 's',
+        }
+    }
+
+    /// Returns true for hearts and diamonds
+    pub fn is_red(&self) -> bool {
+        matches!(self, Suit::Hearts | Suit::Diamonds)
+    }
+}
+
+impl FromStr for Suit {
+    type Err = String;
+
+    /// Parses a suit letter ("s", "h", "d", "c", case-insensitive) or the emoji
+    /// produced by `Display`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let symbol = value.trim_end_matches('\u{fe0f}');
+        match symbol.to_ascii_lowercase().as_str() {
+            "s" | "♠" => Ok(Suit::Spades),
+            "h" | "❤" | "♥" => Ok(Suit::Hearts),
+            "d" | "♦" => Ok(Suit::Diamonds),
+            "c" | "♣" => Ok(Suit::Clubs),
+            _ => Err(format!("Unknown suit '{}'", value)),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Suit::Hearts => "❤️",
+            Suit::Diamonds => "♦️",
+            Suit::Clubs => "♣️",
+            Suit::Spades => "♠️",
+        };
+        f.write_str(symbol)
+    }
+}
+
+#[derive(Debug, Eq, Hash, PartialEq, Clone)]
 pub struct Card {
     pub rank: Rank,
     pub suit: Suit,
@@ -75,12 +155,72 @@ impl Card {
             suit,
         }
     }
+}
+
+/// How cards are rendered as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CardStyle {
+    /// Rank and emoji suit, e.g. "10♠️" (the `Display` format)
+    #[default]
+    Emoji,
+    /// Compact ASCII notation, e.g. "Ts"
+    Ascii,
+}
+
+impl Card {
+    /// Renders the card in the given style
+    pub fn render(&self, style: CardStyle) -> String {
+        match style {
+            CardStyle::Emoji => self.to_string(),
+            CardStyle::Ascii => format!("{}{}", self.rank.symbol(), self.suit.letter()),
+        }
+    }
+}
 
-    pub fn to_string(&self) -> String {
-        format!("{}{}", self.rank.to_string(), self.suit.to_string())
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
     }
 }
 
+impl FromStr for Card {
+    type Err = String;
+
+    /// Parses a rank followed by a suit, e.g. "As", "Td", "10h" or "8♥️"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let rank_len = if value.starts_with("10") { 2 } else {
+            value.chars().next().map_or(0, char::len_utf8)
+        };
+        if rank_len == 0 || rank_len == value.len() {
+            return Err(format!("Invalid card '{}'", value));
+        }
+        let (rank, suit) = value.split_at(rank_len);
+        Ok(Card::new(rank.parse()?, suit.parse()?))
+    }
+}
+
+/// Parses a list of cards separated by commas and/or whitespace, e.g. "As Kd" or "A,T,6"
+///
+/// Rank-only entries are given the first suit (spades, hearts, diamonds, clubs)
+/// not already used by that rank earlier in the list, so "8,8" becomes "8s,8h".
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, String> {
+    let mut cards: Vec<Card> = Vec::new();
+    for token in s.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+        let card = match token.parse::<Card>() {
+            Ok(card) => card,
+            Err(card_err) => {
+                let rank: Rank = token.parse().map_err(|_| card_err)?;
+                let used = cards.iter().filter(|card| card.rank == rank).count();
+                let suit = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs][used % 4].clone();
+                Card::new(rank, suit)
+            }
+        };
+        cards.push(card);
+    }
+    Ok(cards)
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -134,4 +274,64 @@ mod tests {
 
         assert_eq!(card.to_string(), "A♣️");
     }
+
+    #[test]
+    fn test_card_render_ascii() {
+        assert_eq!(Card::new(Rank::Ace, Suit::Spades).render(CardStyle::Ascii), "As");
+        assert_eq!(Card::new(Rank::Ten, Suit::Diamonds).render(CardStyle::Ascii), "Td");
+        assert_eq!(Card::new(Rank::Ten, Suit::Diamonds).render(CardStyle::Emoji), "10♦️");
+    }
+
+    #[test]
+    fn test_parse_rank() {
+        assert_eq!("A".parse::<Rank>(), Ok(Rank::Ace));
+        assert_eq!("t".parse::<Rank>(), Ok(Rank::Ten));
+        assert_eq!("10".parse::<Rank>(), Ok(Rank::Ten));
+        assert_eq!("q".parse::<Rank>(), Ok(Rank::Queen));
+        assert!("1".parse::<Rank>().is_err());
+        assert!("AK".parse::<Rank>().is_err());
+    }
+
+    #[test]
+    fn test_parse_card() {
+        assert_eq!("As".parse::<Card>(), Ok(Card::new(Rank::Ace, Suit::Spades)));
+        assert_eq!("Td".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Diamonds)));
+        assert_eq!("10H".parse::<Card>(), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!("8c".parse::<Card>(), Ok(Card::new(Rank::Eight, Suit::Clubs)));
+        assert!("A".parse::<Card>().is_err());
+        assert!("Ax".parse::<Card>().is_err());
+        assert!("".parse::<Card>().is_err());
+    }
+
+    #[test]
+    fn test_card_round_trips_through_both_styles() {
+        for rank in Rank::iter() {
+            for suit in Suit::iter() {
+                let card = Card::new(rank.clone(), suit);
+                assert_eq!(card.render(CardStyle::Ascii).parse::<Card>(), Ok(card.clone()));
+                assert_eq!(card.to_string().parse::<Card>(), Ok(card));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_cards() {
+        let cards = parse_cards("As, Kd 7h").unwrap();
+        assert_eq!(cards, vec![
+            Card::new(Rank::Ace, Suit::Spades),
+            Card::new(Rank::King, Suit::Diamonds),
+            Card::new(Rank::Seven, Suit::Hearts),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_rank_only_cards() {
+        let cards = parse_cards("8,8,T").unwrap();
+        assert_eq!(cards, vec![
+            Card::new(Rank::Eight, Suit::Spades),
+            Card::new(Rank::Eight, Suit::Hearts),
+            Card::new(Rank::Ten, Suit::Spades),
+        ]);
+        assert!(parse_cards("8,X").is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,192 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::game_settings::GameSettings;
+
+/// File formats a table configuration can be stored in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Picks the format from a file's `.toml` or `.json` extension
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|extension| extension.to_str()).map(str::to_lowercase).as_deref() {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            _ => Err(format!("{} is not a .toml or .json file", path.display())),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Json => "JSON",
+        })
+    }
+}
+
+impl GameSettings {
+    /// Reads settings from TOML or JSON text and validates them
+    ///
+    /// Keys that are left out keep their default value. Errors name the key at
+    /// fault, e.g. "rules.blackjack_payout: Invalid payout '3-2', expected a
+    /// ratio such as 3:2".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blackjack_engine::config::ConfigFormat;
+    /// use blackjack_engine::game_settings::GameSettings;
+    ///
+    /// let settings = GameSettings::from_config_str(r#"
+    ///     deck_count = 2
+    ///     min_bet = "$25"
+    ///
+    ///     [rules]
+    ///     dealer_hits_soft_17 = true
+    /// "#, ConfigFormat::Toml).unwrap();
+    /// assert_eq!(settings.deck_count, 2);
+    /// assert!(settings.rules.dealer_hits_soft_17);
+    /// ```
+    pub fn from_config_str(text: &str, format: ConfigFormat) -> Result<Self, String> {
+        let settings: GameSettings = match format {
+            ConfigFormat::Toml => serde_path_to_error::deserialize(toml::Deserializer::new(text))
+                .map_err(|e| keyed_error(&e.path().to_string(), e.inner().message()))?,
+            ConfigFormat::Json => serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(text))
+                .map_err(|e| keyed_error(&e.path().to_string(), &e.inner().to_string()))?,
+        };
+        settings.validate_with_key().map_err(|(key, message)| keyed_error(&key, &message))?;
+        Ok(settings)
+    }
+
+    /// Writes every setting as TOML or JSON text
+    pub fn to_config_string(&self, format: ConfigFormat) -> Result<String, String> {
+        match format {
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Loads settings from a `.toml` or `.json` file
+    ///
+    /// # Returns
+    /// `Err` naming the file, and the key where possible, if it cannot be read,
+    /// parsed or validated
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::from_config_str(&text, format).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Saves the settings to a `.toml` or `.json` file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let text = self.to_config_string(ConfigFormat::from_path(path)?)?;
+        fs::write(path, text).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+}
+
+/// Prefixes a message with the key it is about, unless it is about the whole file
+fn keyed_error(key: &str, message: &str) -> String {
+    if key.is_empty() || key == "." {
+        message.trim().to_string()
+    } else {
+        format!("{}: {}", key, message.trim())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counting::CountingSystem;
+    use crate::game_settings::TablePreset;
+    use crate::money::Money;
+    use crate::rules::Payout;
+    use crate::shoe::ShuffleMode;
+    use crate::shuffle::CasinoShuffle;
+    use crate::side_bet::PerfectPairsPays;
+
+    #[test]
+    fn test_round_trip() {
+        let mut settings = GameSettings::from_preset("Instructor".to_string(), TablePreset::SingleDeckSixToFive);
+        settings.counting_system = CountingSystem::OmegaII;
+        settings.min_bet = Money::from_cents(1050);
+        settings.bet_increment = Money::from_cents(50);
+        settings.side_bets.perfect_pairs = Some(PerfectPairsPays::new());
+        settings.casino_shuffle = Some(CasinoShuffle { plug: false, ..CasinoShuffle::new() });
+        for format in [ConfigFormat::Toml, ConfigFormat::Json] {
+            let text = settings.to_config_string(format).unwrap();
+            assert_eq!(GameSettings::from_config_str(&text, format).unwrap(), settings, "{}", format);
+        }
+    }
+
+    #[test]
+    fn test_missing_keys_use_defaults() {
+        let settings = GameSettings::from_config_str(r#"{"penetration": 0.8, "rules": {"late_surrender": true}}"#, ConfigFormat::Json).unwrap();
+        assert_eq!(settings.penetration, 0.8);
+        assert!(settings.rules.late_surrender);
+        assert_eq!(settings.deck_count, 6);
+        assert_eq!(settings.rules.blackjack_payout, Payout::THREE_TO_TWO);
+        assert_eq!(settings.side_bets.perfect_pairs, None);
+
+        let settings = GameSettings::from_config_str("[side_bets.perfect_pairs]\nmixed = \"5:1\"\n", ConfigFormat::Toml).unwrap();
+        let pays = settings.side_bets.perfect_pairs.unwrap();
+        assert_eq!(pays.mixed, Payout { numerator: 5, denominator: 1 });
+        assert_eq!(pays.perfect, PerfectPairsPays::new().perfect);
+
+        let settings = GameSettings::from_config_str("[shuffle_mode.continuous]\nbuffer = 20\n", ConfigFormat::Toml).unwrap();
+        assert_eq!(settings.shuffle_mode, ShuffleMode::Continuous { buffer: 20 });
+
+        let settings = GameSettings::from_config_str("[casino_shuffle]\nriffles = 3\n", ConfigFormat::Toml).unwrap();
+        assert_eq!(settings.casino_shuffle, Some(CasinoShuffle { riffles: 3, ..CasinoShuffle::new() }));
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        let error = GameSettings::from_config_str("[rules]\nblackjack_payout = \"3-2\"\n", ConfigFormat::Toml).unwrap_err();
+        assert_eq!(error, "rules.blackjack_payout: Invalid payout '3-2', expected a ratio such as 3:2");
+
+        let error = GameSettings::from_config_str(r#"{"min_bet": "five"}"#, ConfigFormat::Json).unwrap_err();
+        assert!(error.starts_with("min_bet: Invalid amount 'five'"), "{}", error);
+
+        let error = GameSettings::from_config_str("counting_system = \"hi_lo_lo\"\n", ConfigFormat::Toml).unwrap_err();
+        assert!(error.starts_with("counting_system: unknown variant `hi_lo_lo`"), "{}", error);
+
+        let error = GameSettings::from_config_str("[rules]\nsurender = true\n", ConfigFormat::Toml).unwrap_err();
+        assert!(error.starts_with("rules.surender: unknown field `surender`"), "{}", error);
+    }
+
+    #[test]
+    fn test_validation_errors_name_the_key() {
+        let error = GameSettings::from_config_str("penetration = 1.5\n", ConfigFormat::Toml).unwrap_err();
+        assert_eq!(error, "penetration: Penetration must be greater than 0 and at most 1");
+
+        let error = GameSettings::from_config_str("[rules]\nno_hole_card = true\nlate_surrender = true\n", ConfigFormat::Toml).unwrap_err();
+        assert!(error.starts_with("rules.late_surrender: "), "{}", error);
+
+        let error = GameSettings::from_config_str("deck_count = 1\n[shuffle_mode.continuous]\nbuffer = 30\n", ConfigFormat::Toml).unwrap_err();
+        assert_eq!(error, "shuffle_mode: A continuous shuffler can hold out at most half the shoe");
+
+        let error = GameSettings::from_config_str("[shuffle_mode.continuous]\nbuffer = 20\n[casino_shuffle]\n", ConfigFormat::Toml).unwrap_err();
+        assert_eq!(error, "casino_shuffle: A continuous shuffler is never shuffled by hand");
+    }
+
+    #[test]
+    fn test_save_and_load_file() {
+        let path = std::env::temp_dir().join(format!("table-{}.toml", uuid::Uuid::new_v4()));
+        let settings = GameSettings::from_preset("Instructor".to_string(), TablePreset::AtlanticCity);
+        settings.save(&path).unwrap();
+        let loaded = GameSettings::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), settings);
+
+        assert!(GameSettings::load("table.yaml").unwrap_err().contains("is not a .toml or .json file"));
+    }
+}
@@ -0,0 +1,346 @@
+use std::time::Duration;
+use crate::card::Card;
+use crate::counting::CountingSystem;
+use crate::shoe::Shoe;
+
+/// How many cards are shown at a time during a counting drill
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupSize {
+    /// One card at a time
+    Singles,
+    /// Two cards at a time, to practise cancelling pairs
+    Pairs,
+    /// A full round: two cards for each player hand plus two for the dealer
+    Rounds { hands: usize },
+}
+
+impl GroupSize {
+    /// Number of cards dealt per group
+    pub fn card_count(&self) -> usize {
+        match self {
+            GroupSize::Singles => 1,
+            GroupSize::Pairs => 2,
+            GroupSize::Rounds { hands } => (hands + 1) * 2,
+        }
+    }
+}
+
+/// Configuration for a running count drill
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountDrillSettings {
+    pub system: CountingSystem,
+    /// Number of decks in the drill shoe
+    pub deck_count: usize,
+    pub group_size: GroupSize,
+    /// How long the UI should show each group before dealing the next
+    pub pace: Duration,
+    /// Ask for the running count after this many groups
+    pub checkpoint_every: usize,
+    /// Stop after this many cards; `None` deals the whole shoe
+    pub card_limit: Option<usize>,
+}
+
+impl CountDrillSettings {
+    /// Single-deck Hi-Lo drill of single cards with a checkpoint every 10 cards
+    pub fn new(system: CountingSystem) -> Self {
+        Self {
+            system,
+            deck_count: 1,
+            group_size: GroupSize::Singles,
+            pace: Duration::from_millis(1000),
+            checkpoint_every: 10,
+            card_limit: None,
+        }
+    }
+}
+
+/// Result of one running count checkpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckpointResult {
+    /// Cards shown so far in the drill
+    pub cards_seen: usize,
+    pub expected: i32,
+    /// The student's answer, or `None` if the checkpoint was skipped
+    pub answer: Option<i32>,
+    pub response_time: Duration,
+}
+
+impl CheckpointResult {
+    pub fn is_correct(&self) -> bool {
+        self.answer == Some(self.expected)
+    }
+}
+
+/// Accuracy and speed for one drill session
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrillScore {
+    pub system: CountingSystem,
+    pub cards_seen: usize,
+    pub checkpoints: usize,
+    pub correct: usize,
+    /// Total time spent answering checkpoints
+    pub response_time: Duration,
+}
+
+impl DrillScore {
+    /// Fraction of checkpoints answered correctly, 0.0 when there were none
+    pub fn accuracy(&self) -> f64 {
+        if self.checkpoints == 0 {
+            return 0f64;
+        }
+        self.correct as f64 / self.checkpoints as f64
+    }
+
+    /// Average time taken to answer a checkpoint
+    pub fn average_response_time(&self) -> Duration {
+        if self.checkpoints == 0 {
+            return Duration::ZERO;
+        }
+        self.response_time / self.checkpoints as u32
+    }
+}
+
+/// Deals cards from a shoe in groups and asks for the running count at checkpoints
+///
+/// The drill is driven by the caller: deal with `next_group`, wait `pace`, and
+/// when `awaiting_answer` is true ask the student and pass their count to `answer`.
+pub struct CountDrill {
+    pub settings: CountDrillSettings,
+    shoe: Shoe,
+    cards_seen: usize,
+    groups_dealt: usize,
+    awaiting_answer: bool,
+    results: Vec<CheckpointResult>,
+}
+
+impl CountDrill {
+    /// Creates a drill with a freshly shuffled shoe
+    pub fn new(settings: CountDrillSettings) -> Self {
+        let mut shoe = Shoe::new(settings.deck_count);
+        shoe.shuffle();
+        Self::with_shoe(settings, shoe)
+    }
+
+    /// Creates a drill that deals from an existing shoe, e.g. one built by a `Scenario`
+    pub fn with_shoe(settings: CountDrillSettings, shoe: Shoe) -> Self {
+        Self {
+            settings,
+            shoe,
+            cards_seen: 0,
+            groups_dealt: 0,
+            awaiting_answer: false,
+            results: Vec::new(),
+        }
+    }
+
+    /// Deals the next group of cards, or `None` once the drill is over
+    ///
+    /// Dealing past an unanswered checkpoint records it as skipped.
+    pub fn next_group(&mut self) -> Option<Vec<Card>> {
+        if self.is_finished() {
+            return None;
+        }
+        if self.awaiting_answer {
+            self.record(None, Duration::ZERO);
+        }
+
+        let remaining = self.settings.card_limit
+            .map_or(usize::MAX, |limit| limit - self.cards_seen);
+        let group: Vec<Card> = (0..self.settings.group_size.card_count().min(remaining))
+            .map_while(|_| self.shoe.draw_card())
+            .collect();
+        if group.is_empty() {
+            return None;
+        }
+
+        self.cards_seen += group.len();
+        self.groups_dealt += 1;
+        self.awaiting_answer = self.groups_dealt.is_multiple_of(self.settings.checkpoint_every.max(1))
+            || self.is_finished();
+        Some(group)
+    }
+
+    /// True when no more cards will be dealt
+    pub fn is_finished(&self) -> bool {
+        self.shoe.cards.is_empty()
+            || self.settings.card_limit.is_some_and(|limit| self.cards_seen >= limit)
+    }
+
+    /// True when the student should be asked for the running count
+    pub fn awaiting_answer(&self) -> bool {
+        self.awaiting_answer
+    }
+
+    /// Actual running count of the cards dealt so far
+    pub fn running_count(&self) -> i32 {
+        self.shoe.running_count(self.settings.system)
+    }
+
+    /// Grades the student's running count for the current checkpoint
+    ///
+    /// # Returns
+    /// `Err` if no checkpoint is waiting for an answer
+    pub fn answer(&mut self, count: i32, response_time: Duration) -> Result<CheckpointResult, String> {
+        if !self.awaiting_answer {
+            return Err("No running count has been asked for yet".to_string());
+        }
+        Ok(self.record(Some(count), response_time))
+    }
+
+    fn record(&mut self, answer: Option<i32>, response_time: Duration) -> CheckpointResult {
+        let result = CheckpointResult {
+            cards_seen: self.cards_seen,
+            expected: self.running_count(),
+            answer,
+            response_time,
+        };
+        self.results.push(result.clone());
+        self.awaiting_answer = false;
+        result
+    }
+
+    /// Every checkpoint graded so far
+    pub fn results(&self) -> &[CheckpointResult] {
+        &self.results
+    }
+
+    /// Score for the drill so far
+    pub fn score(&self) -> DrillScore {
+        DrillScore {
+            system: self.settings.system,
+            cards_seen: self.cards_seen,
+            checkpoints: self.results.len(),
+            correct: self.results.iter().filter(|result| result.is_correct()).count(),
+            response_time: self.results.iter().map(|result| result.response_time).sum(),
+        }
+    }
+}
+
+/// Drill scores kept across sessions to show progress over time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrillHistory {
+    pub sessions: Vec<DrillScore>,
+}
+
+impl DrillHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, score: DrillScore) {
+        self.sessions.push(score);
+    }
+
+    /// Sessions drilled with the given counting system, oldest first
+    pub fn sessions_for(&self, system: CountingSystem) -> Vec<&DrillScore> {
+        self.sessions.iter().filter(|score| score.system == system).collect()
+    }
+
+    /// Combined score of every session drilled with the given counting system
+    pub fn total_for(&self, system: CountingSystem) -> DrillScore {
+        self.sessions_for(system).iter().fold(
+            DrillScore {
+                system,
+                cards_seen: 0,
+                checkpoints: 0,
+                correct: 0,
+                response_time: Duration::ZERO,
+            },
+            |total, score| DrillScore {
+                system,
+                cards_seen: total.cards_seen + score.cards_seen,
+                checkpoints: total.checkpoints + score.checkpoints,
+                correct: total.correct + score.correct,
+                response_time: total.response_time + score.response_time,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn stacked_shoe(cards: &str) -> Shoe {
+        let mut shoe = Shoe::new(1);
+        let top: Vec<Card> = parse_cards(cards).unwrap()
+            .iter()
+            .map(|card| shoe.remove_card(card).unwrap())
+            .collect();
+        shoe.stack(top);
+        shoe
+    }
+
+    #[test]
+    fn test_checkpoints_follow_group_count() {
+        let mut settings = CountDrillSettings::new(CountingSystem::HiLo);
+        settings.group_size = GroupSize::Pairs;
+        settings.checkpoint_every = 2;
+        let mut drill = CountDrill::with_shoe(settings, stacked_shoe("2s 5h Kd 9c 3d 4c"));
+
+        assert_eq!(drill.next_group().unwrap().len(), 2);
+        assert!(!drill.awaiting_answer());
+        drill.next_group();
+        assert!(drill.awaiting_answer());
+
+        let result = drill.answer(1, Duration::from_secs(2)).unwrap();
+        assert_eq!(result.expected, 1);
+        assert!(result.is_correct());
+        assert!(drill.answer(1, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_skipped_checkpoint_is_scored_as_wrong() {
+        let mut settings = CountDrillSettings::new(CountingSystem::HiLo);
+        settings.checkpoint_every = 1;
+        let mut drill = CountDrill::with_shoe(settings, stacked_shoe("2s 5h Kd"));
+
+        drill.next_group();
+        drill.answer(1, Duration::from_secs(1)).unwrap();
+        drill.next_group();
+        drill.next_group();
+        drill.answer(0, Duration::from_secs(3)).unwrap();
+
+        let score = drill.score();
+        assert_eq!(score.checkpoints, 3);
+        assert_eq!(score.correct, 1);
+        assert_eq!(drill.results()[1].answer, None);
+        assert_eq!(score.average_response_time(), Duration::from_secs(4) / 3);
+    }
+
+    #[test]
+    fn test_card_limit_ends_drill_with_checkpoint() {
+        let mut settings = CountDrillSettings::new(CountingSystem::HiLo);
+        settings.group_size = GroupSize::Rounds { hands: 1 };
+        settings.card_limit = Some(6);
+        let mut drill = CountDrill::new(settings);
+
+        assert_eq!(drill.next_group().unwrap().len(), 4);
+        assert_eq!(drill.next_group().unwrap().len(), 2);
+        assert!(drill.is_finished());
+        assert!(drill.awaiting_answer());
+        assert!(drill.next_group().is_none());
+    }
+
+    #[test]
+    fn test_history_tracks_systems_separately() {
+        let mut history = DrillHistory::new();
+        let score = |system, correct| DrillScore {
+            system,
+            cards_seen: 52,
+            checkpoints: 5,
+            correct,
+            response_time: Duration::from_secs(10),
+        };
+        history.record(score(CountingSystem::HiLo, 3));
+        history.record(score(CountingSystem::HiLo, 5));
+        history.record(score(CountingSystem::KO, 1));
+
+        let total = history.total_for(CountingSystem::HiLo);
+        assert_eq!(total.checkpoints, 10);
+        assert_eq!(total.accuracy(), 0.8);
+        assert_eq!(total.average_response_time(), Duration::from_secs(2));
+        assert_eq!(history.sessions_for(CountingSystem::KO).len(), 1);
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use crate::card::{Card, Rank, Suit};
+
+/// Card counting systems supported by the trainer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountingSystem {
+    #[default]
+    HiLo,
+    KO,
+    HiOptI,
+    HiOptII,
+    OmegaII,
+    ZenCount,
+    Red7,
+}
+
+impl CountingSystem {
+    /// Returns the count value (tag) this system assigns to a card
+    pub fn tag(&self, card: &Card) -> i32 {
+        let rank = &card.rank;
+        match self {
+            CountingSystem::HiLo => match rank {
+                Rank::Two | Rank::Three | Rank::Four | Rank::Five | Rank::Six => 1,
+                Rank::Seven | Rank::Eight | Rank::Nine => 0,
+                _ => -1,
+            },
+            CountingSystem::KO => match rank {
+                Rank::Two | Rank::Three | Rank::Four | Rank::Five | Rank::Six | Rank::Seven => 1,
+                Rank::Eight | Rank::Nine => 0,
+                _ => -1,
+            },
+            CountingSystem::HiOptI => match rank {
+                Rank::Three | Rank::Four | Rank::Five | Rank::Six => 1,
+                Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => -1,
+                _ => 0,
+            },
+            CountingSystem::HiOptII => match rank {
+                Rank::Two | Rank::Three | Rank::Six | Rank::Seven => 1,
+                Rank::Four | Rank::Five => 2,
+                Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => -2,
+                _ => 0,
+            },
+            CountingSystem::OmegaII => match rank {
+                Rank::Two | Rank::Three | Rank::Seven => 1,
+                Rank::Four | Rank::Five | Rank::Six => 2,
+                Rank::Nine => -1,
+                Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => -2,
+                _ => 0,
+            },
+            CountingSystem::ZenCount => match rank {
+                Rank::Two | Rank::Three | Rank::Seven => 1,
+                Rank::Four | Rank::Five | Rank::Six => 2,
+                Rank::Ace => -1,
+                Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => -2,
+                _ => 0,
+            },
+            CountingSystem::Red7 => match rank {
+                Rank::Two | Rank::Three | Rank::Four | Rank::Five | Rank::Six => 1,
+                Rank::Seven => match card.suit {
+                    Suit::Hearts | Suit::Diamonds => 1,
+                    Suit::Clubs | Suit::Spades => 0,
+                },
+                Rank::Eight | Rank::Nine => 0,
+                _ => -1,
+            },
+        }
+    }
+
+    /// Returns true if a full deck counts to zero under this system
+    pub fn is_balanced(&self) -> bool {
+        !matches!(self, CountingSystem::KO | CountingSystem::Red7)
+    }
+
+    /// Running count at the start of a shoe of `decks` decks
+    ///
+    /// Balanced systems start at zero. KO starts at 4 - 4 × decks and Red 7 at
+    /// -2 × decks so that the pivot lands on the usual key counts.
+    pub fn initial_running_count(&self, decks: usize) -> i32 {
+        match self {
+            CountingSystem::KO => 4 - 4 * decks as i32,
+            CountingSystem::Red7 => -2 * decks as i32,
+            _ => 0,
+        }
+    }
+
+    /// Sum of the tags of `cards`
+    pub fn count_cards(&self, cards: &[Card]) -> i32 {
+        cards.iter().map(|card| self.tag(card)).sum()
+    }
+}
+
+impl fmt::Display for CountingSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CountingSystem::HiLo => "Hi-Lo",
+            CountingSystem::KO => "KO",
+            CountingSystem::HiOptI => "Hi-Opt I",
+            CountingSystem::HiOptII => "Hi-Opt II",
+            CountingSystem::OmegaII => "Omega II",
+            CountingSystem::ZenCount => "Zen Count",
+            CountingSystem::Red7 => "Red 7",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Converts a running count to a true count (running count per deck remaining)
+///
+/// Decks remaining is floored at a quarter deck so the last few cards of a
+/// shoe do not produce absurd counts.
+pub fn true_count(running_count: i32, decks_remaining: f64) -> f64 {
+    running_count as f64 / decks_remaining.max(0.25)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+    use crate::shoe::Shoe;
+
+    #[test]
+    fn test_hi_lo_tags() {
+        let system = CountingSystem::HiLo;
+        assert_eq!(system.tag(&Card::new(Rank::Two, Suit::Spades)), 1);
+        assert_eq!(system.tag(&Card::new(Rank::Eight, Suit::Spades)), 0);
+        assert_eq!(system.tag(&Card::new(Rank::King, Suit::Spades)), -1);
+        assert_eq!(system.tag(&Card::new(Rank::Ace, Suit::Spades)), -1);
+    }
+
+    #[test]
+    fn test_red_seven_depends_on_colour() {
+        let system = CountingSystem::Red7;
+        assert_eq!(system.tag(&Card::new(Rank::Seven, Suit::Hearts)), 1);
+        assert_eq!(system.tag(&Card::new(Rank::Seven, Suit::Clubs)), 0);
+    }
+
+    #[test]
+    fn test_full_deck_counts() {
+        let deck = Shoe::new(1).cards;
+        for system in CountingSystem::iter() {
+            let expected = match system {
+                CountingSystem::KO => 4,
+                CountingSystem::Red7 => 2,
+                _ => 0,
+            };
+            assert_eq!(system.count_cards(&deck), expected, "{}", system);
+            assert_eq!(system.is_balanced(), expected == 0);
+        }
+    }
+
+    #[test]
+    fn test_unbalanced_initial_counts() {
+        assert_eq!(CountingSystem::KO.initial_running_count(6), -20);
+        assert_eq!(CountingSystem::Red7.initial_running_count(6), -12);
+        assert_eq!(CountingSystem::HiLo.initial_running_count(6), 0);
+    }
+
+    #[test]
+    fn test_true_count() {
+        assert_eq!(true_count(6, 3.0), 2.0);
+        assert_eq!(true_count(-3, 1.5), -2.0);
+    }
+}
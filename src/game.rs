@@ -1,10 +1,21 @@
-use crate::card::{Card, Rank, Suit};
-use crate::game::GameAction::{Double, Hit, Split, Stand};
+use std::collections::VecDeque;
+use std::fmt;
+use crate::game::GameAction::{Double, Hit, Split, Stand, Surrender, Switch};
 use crate::game::GameState::WaitingToDeal;
+use crate::analytics::KellyBet;
+use crate::card::Rank;
+use crate::counting::true_count;
 use crate::game_settings::GameSettings;
+use crate::rules::GameVariant;
 use crate::hand::{Hand, HandOutcome};
+use crate::ledger::TransactionKind;
+use crate::money::Money;
 use crate::player::Player;
-use crate::shoe::Shoe;
+use crate::side_bet::{SideBet, SideBetKind, SideBetOutcome};
+use crate::spanish21::twenty_one_bonus;
+use crate::shoe::{Shoe, ShuffleMode};
+use crate::shuffle::ShuffleTrace;
+use uuid::Uuid;
 
 pub struct Game {
     pub settings: GameSettings,
@@ -12,6 +23,35 @@ pub struct Game {
     pub player: Player,
     pub dealer: Player,
     pub state: GameState,
+    /// Number of the current round, starting at 1
+    pub round: u64,
+    /// Unique id of the current round, used to group ledger entries
+    pub round_id: Uuid,
+    /// Unique id of this playing session
+    pub session_id: Uuid,
+    /// Most recently completed rounds, oldest first, capped at `settings.round_history_limit`
+    pub history: VecDeque<RoundRecord>,
+    /// Side wagers placed with the current round's bet
+    pub side_bets: Vec<SideBet>,
+    /// Where the discards went in the latest casino shuffle, for following slugs
+    pub last_shuffle: Option<ShuffleTrace>,
+}
+
+/// A completed round kept for review screens
+#[derive(Debug, Clone)]
+pub struct RoundRecord {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    /// Round number within the session
+    pub number: u64,
+    pub dealer_hand: Hand,
+    pub player_hands: Vec<Hand>,
+    /// Side wagers placed with the round's bet, with their outcomes
+    pub side_bets: Vec<SideBet>,
+    /// Net bankroll change over the round
+    pub net: Money,
+    /// Bankroll once the round was settled
+    pub player_bankroll: Money,
 }
 
 impl Game {
@@ -21,12 +61,32 @@ impl Game {
         Game {
             player,
             dealer: Player::new(),
-            shoe: Shoe::new(settings.deck_count as usize),
+            shoe: Shoe::for_variant(settings.deck_count as usize, settings.rules.variant),
             settings,
-            state: GameState::WaitingForBet { player_bankroll: bankroll }
+            state: GameState::WaitingForBet { player_bankroll: bankroll },
+            round: 1,
+            round_id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+            history: VecDeque::new(),
+            side_bets: Vec::new(),
+            last_shuffle: None,
         }
     }
 
+    /// Looks up a completed round still held in the history
+    pub fn find_round(&self, round_id: Uuid) -> Option<&RoundRecord> {
+        self.history.iter().find(|record| record.id == round_id)
+    }
+
+    /// Looks up a player hand from a completed round, along with the round it was played in
+    pub fn find_hand(&self, hand_id: Uuid) -> Option<(&RoundRecord, &Hand)> {
+        self.history.iter().find_map(|record| {
+            record.player_hands.iter()
+                .find(|hand| hand.id == hand_id)
+                .map(|hand| (record, hand))
+        })
+    }
+
     pub fn get_state(&self) -> &GameState {
         &self.state
     }
@@ -35,144 +95,366 @@ impl Game {
         self.shoe.shuffle();
     }
 
-    pub fn accept_user_bet(&mut self, bet: f64) {
-        if self.player.bank_roll < bet {
-            println!("You cannot bet more than you have");
-            return;
+    /// Running count of the shoe under the table's counting system
+    ///
+    /// Counts every card the player has seen. Face-down dealer cards stay out of
+    /// the count during the player's turn and join it when the dealer reveals them.
+    pub fn running_count(&self) -> i32 {
+        let system = self.settings.counting_system;
+        let mut count = self.shoe.running_count(system);
+        if let GameState::PlayerTurn { dealer_hand, dealer_upcard_visible, dealer_hole_card_visible, .. } = &self.state {
+            for (index, card) in dealer_hand.cards.iter().enumerate() {
+                let visible = if index == 0 { *dealer_upcard_visible } else { *dealer_hole_card_visible };
+                if !visible {
+                    count -= system.tag(card);
+                }
+            }
+        }
+        count
+    }
+
+    /// True count of the shoe under the table's counting system
+    pub fn true_count(&self) -> f64 {
+        true_count(self.running_count(), self.shoe.decks_remaining())
+    }
+
+    /// Kelly-criterion bet sizes for the player's bankroll at the current true count
+    ///
+    /// Advisory only: show it next to the bet prompt, then pass the chosen amount
+    /// (e.g. through `GameSettings::largest_bet_up_to`) to `accept_user_bet`.
+    /// `None` for games without a house edge estimate.
+    pub fn kelly_advice(&self) -> Option<KellyBet> {
+        KellyBet::at_true_count(self.player.bank_roll, self.true_count(), &self.settings)
+    }
+
+    /// Places the player's bet for the next round
+    ///
+    /// # Returns
+    /// `Err` with a description if the bet breaks the table limits or exceeds
+    /// the player's bankroll
+    pub fn accept_user_bet(&mut self, bet: Money) -> Result<(), String> {
+        self.accept_user_bet_with_side_bets(bet, &[])
+    }
+
+    /// Places the player's bet for the next round along with any side wagers
+    ///
+    /// Side bets on the opening deal are settled by `deal_initial_cards`. Those
+    /// on the dealer's finished hand, such as Buster Blackjack, are settled when
+    /// the round completes.
+    ///
+    /// # Arguments
+    /// * `bet` - The main wager
+    /// * `side_bets` - Each side bet and the amount wagered on it
+    ///
+    /// # Returns
+    /// `Err` with a description if any wager breaks the table limits, the
+    /// table does not offer a side bet, or the total exceeds the player's bankroll
+    pub fn accept_user_bet_with_side_bets(&mut self, bet: Money, side_bets: &[(SideBetKind, Money)]) -> Result<(), String> {
+        self.settings.check_bet(bet)?;
+        // Blackjack Switch is played with two equal bets
+        let hand_count = if self.settings.rules.variant == GameVariant::BlackjackSwitch { 2 } else { 1 };
+        let mut total = bet * hand_count;
+        for (index, &(kind, amount)) in side_bets.iter().enumerate() {
+            self.settings.check_side_bet(kind, amount)?;
+            if side_bets[..index].iter().any(|&(placed, _)| placed == kind) {
+                return Err(format!("Only one {} bet can be placed per round", kind));
+            }
+            total += amount;
+        }
+        if self.player.bank_roll < total {
+            return Err("You cannot bet more than you have".to_string());
         }
-        self.player.bank_roll -= bet;
-        self.player.hands[0].bet = bet;
-        self.state = WaitingToDeal { player_bet: bet, player_bankroll: self.player.bank_roll }
+        self.player.hands = (0..hand_count).map(|_| Hand::with_bet(bet)).collect();
+        for _ in 0..hand_count {
+            self.player.debit(self.round_id, TransactionKind::Bet, bet);
+        }
+        for &(kind, amount) in side_bets {
+            self.player.debit(self.round_id, TransactionKind::SideBet, amount);
+            self.side_bets.push(SideBet::new(kind, amount));
+        }
+        self.state = WaitingToDeal { player_bet: bet, player_bankroll: self.player.bank_roll };
+        Ok(())
     }
 
+    /// Deals the opening cards and settles any naturals the dealer can already see
+    ///
+    /// Without a hole card the dealer takes a single card now. A player natural
+    /// against a dealer ten or ace then waits for the dealer's second card.
+    ///
+    /// Blackjack Switch hands are both dealt before the dealer, and any naturals
+    /// among them are paid once the round is settled.
     pub fn deal_initial_cards(&mut self) {
-        // Deal two cards to player and dealer
-        self.shoe.ensure_cards_for_players(1);
-        for _ in 0..2 {
-            if let Some(card) = self.shoe.draw_card() {
-                self.player.add_card_to_hand(card, 0);
+        let hole_card = !self.settings.rules.no_hole_card;
+        // Deal two cards to each player hand and the dealer
+        self.shoe.ensure_cards_for_players(self.player.hands.len());
+        for round in 0..2 {
+            for hand_index in 0..self.player.hands.len() {
+                if let Some(card) = self.shoe.draw_card() {
+                    self.player.add_card_to_hand(card, hand_index);
+                }
             }
-            if let Some(card) = self.shoe.draw_card() {
-                self.dealer.add_card_to_hand(card, 0);
+            if round == 0 || hole_card {
+                if let Some(card) = self.shoe.draw_card() {
+                    self.dealer.add_card_to_hand(card, 0);
+                }
             }
         }
+        self.settle_side_bets(false);
 
-        if self.player.hands[0].is_natural_blackjack() {
+        if self.settings.rules.variant == GameVariant::BlackjackSwitch {
             if self.dealer.hands[0].is_natural_blackjack() {
-                // push, add the bet back to player bankroll
-                self.player.bank_roll += self.player.hands[0].bet;
-                self.player.hands[0].outcome = Option::from(HandOutcome::Push);
+                self.determine_winner_and_complete_round();
+                return;
+            }
+            self.state = self.player_turn(0);
+            return;
+        }
 
-                self.state = GameState::RoundComplete {
+        if self.player.hands[0].is_natural_blackjack() {
+            let upcard_value = self.dealer.hands[0].best_value();
+            if !hole_card && upcard_value >= 10 {
+                // The dealer's second card decides between a push and a blackjack
+                self.state = GameState::DealerTurn {
                     dealer_hand: self.dealer.hands[0].clone(),
                     player_hands: self.player.hands.clone(),
                     player_bankroll: self.player.bank_roll
                 };
                 return;
+            }
+            let variant = self.settings.rules.variant;
+            let dealer_natural = self.dealer.hands[0].is_natural_blackjack();
+            if dealer_natural && variant.dealer_wins_ties() && !variant.natural_beats_dealer_natural() {
+                self.player.hands[0].outcome = Option::from(HandOutcome::Loss);
+                self.complete_round();
+                return;
+            }
+            if dealer_natural && !variant.natural_beats_dealer_natural() {
+                // push, add the bet back to player bankroll
+                self.player.credit(self.round_id, TransactionKind::Refund, self.player.hands[0].bet);
+                self.player.hands[0].outcome = Option::from(HandOutcome::Push);
+
+                self.complete_round();
+                return;
             } else {
-                self.player.bank_roll += self.player.hands[0].bet * 2.5;
+                let bet = self.player.hands[0].bet;
+                let payout = bet + self.settings.rules.natural_payout().winnings(bet, self.settings.payout_rounding);
+                self.player.credit(self.round_id, TransactionKind::Payout, payout);
                 self.player.hands[0].outcome = Option::from(HandOutcome::Blackjack);
-                self.state = GameState::RoundComplete {
-                    dealer_hand: self.dealer.hands[0].clone(),
-                    player_hands: self.player.hands.clone(),
-                    player_bankroll: self.player.bank_roll
-                };
+                self.complete_round();
                 return;
             }
         }
 
         if self.dealer.hands[0].is_natural_blackjack() {
             self.player.hands[0].outcome = Option::from(HandOutcome::Loss);
-            self.state = GameState::RoundComplete {
-                dealer_hand: self.dealer.hands[0].clone(),
-                player_hands: self.player.hands.clone(),
-                player_bankroll: self.player.bank_roll
-            };
+            self.complete_round();
             return;
         }
 
-        self.state = GameState::PlayerTurn {
-            dealer_hand: self.dealer.hands[0].clone(),
-            player_hands: self.player.hands.clone(),
-            player_bankroll: self.player.bank_roll,
-            active_hand_index: 0,
+        self.state = self.player_turn(0);
+    }
+
+    /// Settles the side bets that are decided now: those on the opening deal,
+    /// judged against the dealer's upcard, or those on the dealer's finished hand
+    fn settle_side_bets(&mut self, after_dealer: bool) {
+        let player_cards = &self.player.hands[0].cards;
+        let dealer_hand = if after_dealer {
+            self.dealer.hands[0].clone()
+        } else {
+            Hand::with_card(self.dealer.hands[0].cards[0].clone())
+        };
+        let mut credits = Vec::new();
+        let due = self.side_bets.iter_mut()
+            .filter(|side_bet| side_bet.outcome.is_none() && side_bet.kind.settles_after_dealer() == after_dealer);
+        for side_bet in due {
+            let outcome = match self.settings.side_bets.evaluate(side_bet.kind, player_cards, &dealer_hand) {
+                Some((hand, pays)) => {
+                    let winnings = pays.winnings(side_bet.amount, self.settings.payout_rounding);
+                    credits.push(side_bet.amount + winnings);
+                    SideBetOutcome::Win { hand, pays, winnings }
+                }
+                None => SideBetOutcome::Loss,
+            };
+            side_bet.outcome = Some(outcome);
+        }
+        for amount in credits {
+            self.player.credit(self.round_id, TransactionKind::Payout, amount);
         }
     }
 
-    pub fn process_player_action(&mut self, action: GameAction, hand_index: usize) {
+    /// Checks whether the player may take `action` on the hand at `hand_index`
+    ///
+    /// Doubles and splits add a second bet equal to the hand's current bet. That
+    /// bet must be covered by the bankroll, but it may take the hand over the
+    /// table maximum.
+    ///
+    /// # Returns
+    /// `Err` with a description if the action is not allowed
+    pub fn check_action(&self, action: GameAction, hand_index: usize) -> Result<(), String> {
+        let rules = &self.settings.rules;
+        let hand = self.player.hands.get(hand_index)
+            .ok_or_else(|| format!("There is no hand {}", hand_index + 1))?;
+        let is_split = hand.split_from.is_some();
+        let split_aces = is_split && hand.cards.first().is_some_and(|card| card.rank == Rank::Ace);
+        let spanish = rules.variant == GameVariant::Spanish21;
+
         match action {
+            Switch => {
+                if rules.variant != GameVariant::BlackjackSwitch {
+                    return Err("Switching is only offered at Blackjack Switch tables".to_string());
+                }
+                let first_hand_in_play = matches!(self.state, GameState::PlayerTurn { active_hand_index: 0, .. });
+                let two_card_hands = self.player.hands.len() == 2
+                    && self.player.hands.iter().all(|hand| hand.cards.len() == 2 && !hand.doubled);
+                if !first_hand_in_play || !two_card_hands {
+                    return Err("You can only switch before playing either hand".to_string());
+                }
+                if self.player.hands[0].switched {
+                    return Err("The second cards have already been switched".to_string());
+                }
+            }
+            // Spanish 21 lets a doubled hand be rescued by surrendering the original bet
+            Surrender if spanish && hand.doubled => {}
+            // A Pontoon hand that has bought a card may still twist
+            Hit if rules.variant == GameVariant::Pontoon && hand.doubled => {}
+            Hit | Double | Split | Surrender if hand.doubled => {
+                return Err("A doubled hand can only stand".to_string());
+            }
+            Hit if split_aces && !rules.hit_split_aces => {
+                return Err("Split aces receive one card only".to_string());
+            }
+            Stand => {
+                if let Some(minimum) = rules.variant.minimum_stand().filter(|&minimum| hand.best_value() < minimum) {
+                    return Err(format!("You cannot stick below {} in {}", minimum, rules.variant));
+                }
+            }
+            Double => {
+                if split_aces && !rules.hit_split_aces {
+                    return Err("Split aces receive one card only".to_string());
+                }
+                // Spanish 21 allows doubling on any number of cards
+                if hand.cards.len() != 2 && !spanish {
+                    return Err("You can only double on your first two cards".to_string());
+                }
+                if is_split && !rules.double_after_split {
+                    return Err("Doubling after a split is not allowed at this table".to_string());
+                }
+                if !rules.double_on.allows(hand.best_value(), hand.is_soft()) {
+                    return Err(format!("This table only allows doubling on {}", rules.double_on));
+                }
+            }
+            Surrender => {
+                if !rules.late_surrender {
+                    return Err("Surrender is not offered at this table".to_string());
+                }
+                if is_split || hand.cards.len() != 2 {
+                    return Err("You can only surrender your first two cards".to_string());
+                }
+            }
+            Split => {
+                if !hand.can_split() {
+                    return Err("Only a pair can be split".to_string());
+                }
+                let root = hand.split_from.unwrap_or(hand.id);
+                let split_hands = self.player.hands.iter().filter(|other| other.split_from == Some(root)).count();
+                if split_hands.max(1) >= rules.max_split_hands as usize {
+                    return Err(format!("You cannot split into more than {} hands", rules.max_split_hands));
+                }
+                if split_aces && !rules.resplit_aces {
+                    return Err("Aces cannot be resplit at this table".to_string());
+                }
+            }
+            _ => {}
+        }
+        let free = self.is_free_action(action, hand_index);
+        if (action == Double || action == Split) && !free && self.player.bank_roll < hand.bet {
+            return Err(format!(
+                "You need {} to {} but only have {}",
+                hand.bet, action.to_string().to_lowercase(), self.player.bank_roll
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the house puts up the extra bet for `action` on the hand at
+    /// `hand_index`: in Free Bet, doubling a two-card hard 9, 10 or 11, or
+    /// splitting any pair but tens
+    pub fn is_free_action(&self, action: GameAction, hand_index: usize) -> bool {
+        let Some(hand) = self.player.hands.get(hand_index) else {
+            return false;
+        };
+        if self.settings.rules.variant != GameVariant::FreeBet {
+            return false;
+        }
+        match action {
+            Double => hand.cards.len() == 2 && !hand.is_soft() && (9..=11).contains(&hand.best_value()),
+            Split => hand.can_split() && hand.cards[0].rank.value()[0] != 10,
+            _ => false,
+        }
+    }
+
+    /// Applies the player's action to the hand at `hand_index`
+    ///
+    /// # Returns
+    /// `Err` with a description if the action is not allowed, see `check_action`
+    pub fn process_player_action(&mut self, action: GameAction, hand_index: usize) -> Result<(), String> {
+        self.check_action(action, hand_index)?;
+        let free = self.is_free_action(action, hand_index);
+
+        match action {
+            Switch => {
+                let first = self.player.hands[0].cards.pop().unwrap();
+                let second = self.player.hands[1].cards.pop().unwrap();
+                self.player.add_card_to_hand(second, 0);
+                self.player.add_card_to_hand(first, 1);
+                self.player.hands[0].switched = true;
+                self.player.hands[1].switched = true;
+                self.state = self.player_turn(0);
+            }
+            Surrender => {
+                let refund = self.player.hands[hand_index].bet.mul_ratio(1, 2, self.settings.payout_rounding);
+                self.player.credit(self.round_id, TransactionKind::Refund, refund);
+                self.player.hands[hand_index].outcome = Option::from(HandOutcome::Surrender);
+                self.finish_hand(hand_index);
+            }
             Hit => {
                 if let Some(card) = self.shoe.draw_card() {
                     self.player.add_card_to_hand(card, hand_index);
                     if self.player.hands[hand_index].is_busted() {
                         self.player.hands[hand_index].outcome = Option::from(HandOutcome::Loss);
-                        if self.player.hands.len() > hand_index + 1 {
-                            // If there is another hand, it was split and needs at least one
-                            // more card
-                            if let Some(card) = self.shoe.draw_card() {
-                                self.player.add_card_to_hand(card, hand_index + 1);
-                            }
-                            self.state = GameState::PlayerTurn {
-                                dealer_hand: self.dealer.hands[0].clone(),
-                                player_hands: self.player.hands.clone(),
-                                player_bankroll: self.player.bank_roll,
-                                active_hand_index: hand_index + 1
-                            };
-                            return;
-                        }
-                        self.state = GameState::RoundComplete {
-                            dealer_hand: self.dealer.hands[0].clone(),
-                            player_hands: self.player.hands.clone(),
-                            player_bankroll: self.player.bank_roll
-                        };
-                        return;
+                        self.finish_hand(hand_index);
+                        return Ok(());
+                    }
+
+                    if self.is_charlie(hand_index) {
+                        self.settle_charlie(hand_index);
+                        return Ok(());
                     }
 
                     if self.player.hands[hand_index].is_blackjack() {
                         if self.player.hands.len() > hand_index + 1 {
-                            // If there is another hand, it was split and needs at least one
-                            // more card
-                            if let Some(card) = self.shoe.draw_card() {
-                                self.player.add_card_to_hand(card, hand_index + 1);
-                            }
-                            self.state = GameState::PlayerTurn {
-                                dealer_hand: self.dealer.hands[0].clone(),
-                                player_hands: self.player.hands.clone(),
-                                player_bankroll: self.player.bank_roll,
-                                active_hand_index: hand_index + 1
-                            };
-                            return;
+                            // If there is another hand, play moves on to it
+                            self.deal_second_card(hand_index + 1);
+                            self.state = self.player_turn(hand_index + 1);
+                            return Ok(());
                         }
                         self.state = GameState::DealerTurn{
                             dealer_hand: self.dealer.hands[0].clone(),
                             player_hands: self.player.hands.clone(),
                             player_bankroll: self.player.bank_roll
                         };
-                        return;
+                        return Ok(());
                     }
 
-                    self.state = GameState::PlayerTurn {
-                        dealer_hand: self.dealer.hands[0].clone(),
-                        player_hands: self.player.hands.clone(),
-                        player_bankroll: self.player.bank_roll,
-                        active_hand_index: hand_index
-                    }
+                    self.state = self.player_turn(hand_index);
                 }
             },
             Stand => {
                 if self.player.hands.len() > hand_index + 1 {
-                    // If there is another hand, it was split and needs at least one
-                    // more card
-                    if let Some(card) = self.shoe.draw_card() {
-                        self.player.add_card_to_hand(card, hand_index + 1);
-                    }
-                    self.state = GameState::PlayerTurn {
-                        dealer_hand: self.dealer.hands[0].clone(),
-                        player_hands: self.player.hands.clone(),
-                        player_bankroll: self.player.bank_roll,
-                        active_hand_index: hand_index + 1
-                    };
-                    return;
+                    // If there is another hand, play moves on to it
+                    self.deal_second_card(hand_index + 1);
+                    self.state = self.player_turn(hand_index + 1);
+                    return Ok(());
                 }
                 self.state = GameState::DealerTurn {
                     dealer_hand: self.dealer.hands[0].clone(),
@@ -183,19 +465,27 @@ impl Game {
             Double => {
                 if let Some(card) = self.shoe.draw_card() {
                     self.player.add_card_to_hand(card, hand_index);
-                    self.player.bank_roll -= self.player.hands[hand_index].bet;
-                    self.player.hands[hand_index].bet = self.player.hands[hand_index].bet * 2f64;
+                    let bet = self.player.hands[hand_index].bet;
+                    if free {
+                        self.player.hands[hand_index].free_bet += bet;
+                    } else {
+                        self.player.debit(self.round_id, TransactionKind::Double, bet);
+                    }
+                    self.player.hands[hand_index].double_bet();
+                    if self.is_charlie(hand_index) {
+                        self.settle_charlie(hand_index);
+                        return Ok(());
+                    }
+                    if self.settings.rules.variant.plays_on_after_double() && !self.player.hands[hand_index].is_busted() {
+                        // The player may still rescue a Spanish 21 doubled hand, or
+                        // twist on a bought Pontoon hand, before standing
+                        self.state = self.player_turn(hand_index);
+                        return Ok(());
+                    }
                     if self.player.hands.len() > hand_index + 1 {
-                        if let Some(card) = self.shoe.draw_card() {
-                            self.player.add_card_to_hand(card, hand_index + 1);
-                        }
-                        self.state = GameState::PlayerTurn {
-                            dealer_hand: self.dealer.hands[0].clone(),
-                            player_hands: self.player.hands.clone(),
-                            player_bankroll: self.player.bank_roll,
-                            active_hand_index: hand_index + 1
-                        };
-                        return;
+                        self.deal_second_card(hand_index + 1);
+                        self.state = self.player_turn(hand_index + 1);
+                        return Ok(());
                     }
                     self.state = GameState::DealerTurn {
                         dealer_hand: self.dealer.hands[0].clone(),
@@ -205,101 +495,257 @@ impl Game {
                 }
             },
             Split => {
-                // Check if we can split (should have exactly 2 equal cards)
-                if self.player.hands[hand_index].cards.len() == 2
-                    && self.player.hands[hand_index].cards[hand_index].rank == self.player.hands[hand_index].cards[1].rank {
-                    // Take second card from first hand
-                    let split_card = self.player.hands[hand_index].cards.pop().unwrap();
-
-                    // Create new hand with the split card and same bet
-                    let new_bet = self.player.hands[hand_index].bet;
-                    self.player.bank_roll -= new_bet;  // Deduct additional bet for new hand
-
-                    // Add second hand with split card at index + 1
-                    let mut new_hand = Hand::with_card_and_bet(split_card, new_bet);
-                    self.player.hands.insert(hand_index + 1, new_hand);
-
-                    // Draw a card for the first hand only
-                    if let Some(card1) = self.shoe.draw_card() {
-                        self.player.add_card_to_hand(card1, hand_index);
-                        self.state = GameState::PlayerTurn {
-                            dealer_hand: self.dealer.hands[0].clone(),
-                            player_hands: self.player.hands.clone(),
-                            player_bankroll: self.player.bank_roll,
-                            active_hand_index: hand_index
-                        }
-                    }
+                // Take second card from first hand
+                let split_card = self.player.hands[hand_index].cards.pop().unwrap();
+
+                // Create new hand with the split card and same bet
+                let new_bet = self.player.hands[hand_index].bet;
+                if !free {
+                    self.player.debit(self.round_id, TransactionKind::Split, new_bet);  // Additional bet for new hand
+                }
+
+                // Add second hand with split card at index + 1
+                let hand = &mut self.player.hands[hand_index];
+                let root = *hand.split_from.get_or_insert(hand.id);
+                let mut new_hand = Hand::with_card_and_bet(split_card, new_bet);
+                new_hand.split_from = Some(root);
+                if free {
+                    new_hand.free_bet = new_bet;
+                }
+                self.player.hands.insert(hand_index + 1, new_hand);
+
+                // Draw a card for the first hand only
+                if let Some(card1) = self.shoe.draw_card() {
+                    self.player.add_card_to_hand(card1, hand_index);
+                    self.state = self.player_turn(hand_index);
                 }
             }
         }
+        Ok(())
+    }
+
+    /// The player turn state for the hand at `active_hand_index`
+    fn player_turn(&self, active_hand_index: usize) -> GameState {
+        GameState::PlayerTurn {
+            dealer_hand: self.dealer.hands[0].clone(),
+            dealer_upcard_visible: !self.settings.rules.variant.hides_dealer_cards(),
+            dealer_hole_card_visible: self.settings.rules.variant.exposes_hole_card(),
+            player_hands: self.player.hands.clone(),
+            player_bankroll: self.player.bank_roll,
+            active_hand_index,
+        }
+    }
+
+    /// Moves play on once the hand at `hand_index` is settled early by busting,
+    /// surrendering or making a charlie: to the next hand, to the dealer if any
+    /// hand still needs settling, or straight to the end of the round
+    fn finish_hand(&mut self, hand_index: usize) {
+        if self.player.hands.len() > hand_index + 1 {
+            self.deal_second_card(hand_index + 1);
+            self.state = self.player_turn(hand_index + 1);
+        } else if self.player.hands.iter().any(|hand| hand.outcome.is_none() && !hand.is_busted()) {
+            // Earlier hands are still live and must be settled against the dealer
+            self.state = GameState::DealerTurn {
+                dealer_hand: self.dealer.hands[0].clone(),
+                player_hands: self.player.hands.clone(),
+                player_bankroll: self.player.bank_roll
+            };
+        } else {
+            self.complete_round();
+        }
+    }
+
+    /// Returns true if the hand at `hand_index` has reached the table's charlie
+    /// without busting
+    fn is_charlie(&self, hand_index: usize) -> bool {
+        let hand = &self.player.hands[hand_index];
+        self.settings.rules.charlie_cards().is_some_and(|cards| hand.cards.len() >= cards as usize && !hand.is_busted())
+    }
+
+    /// Pays a charlie as a win straight away, whatever the dealer goes on to make
+    fn settle_charlie(&mut self, hand_index: usize) {
+        let winnings = self.settings.rules.charlie_payout();
+        let hand = &mut self.player.hands[hand_index];
+        hand.outcome = Option::from(HandOutcome::Win);
+        let payout = hand.real_bet() + winnings.winnings(hand.bet, self.settings.payout_rounding);
+        self.player.credit(self.round_id, TransactionKind::Payout, payout);
+        self.finish_hand(hand_index);
+    }
+
+    /// Gives a hand created by a split its second card; hands that already have
+    /// two, like the second Blackjack Switch hand, are left alone
+    fn deal_second_card(&mut self, hand_index: usize) {
+        if self.player.hands[hand_index].cards.len() < 2 {
+            if let Some(card) = self.shoe.draw_card() {
+                self.player.add_card_to_hand(card, hand_index);
+            }
+        }
+    }
+
+    /// Returns true while the dealer must draw: on 16 or below, and on soft 17 under H17 rules
+    fn dealer_should_hit(&self) -> bool {
+        let dealer_hand = &self.dealer.hands[0];
+        let dealer_value = dealer_hand.best_value();
+        let hits_soft_17 = self.settings.rules.dealer_hits_soft_17 && dealer_value == 17 && dealer_hand.is_soft();
+        dealer_value <= 16 || hits_soft_17
     }
 
     pub fn next_dealer_turn(&mut self) {
-        match self.state {
-            GameState::DealerTurn { dealer_hand: _, player_hands: _, player_bankroll: _, .. } => {
-                let dealer_value = self.dealer.hands[0].best_value();
-
-                // Dealer must hit on 16 or below
-                if dealer_value <= 16 {
-                    if let Some(card) = self.shoe.draw_card() {
-                        self.dealer.add_card_to_hand(card, 0);
-
-                        // Check if dealer busted
-                        if self.dealer.hands[0].is_busted() {
-                            self.determine_winner_and_complete_round();
-                            return;
-                        }
+        if let GameState::DealerTurn { .. } = self.state {
+            if self.dealer_should_hit() {
+                if let Some(card) = self.shoe.draw_card() {
+                    self.dealer.add_card_to_hand(card, 0);
 
-                        // Continue dealer's turn
-                        self.state = GameState::DealerTurn {
-                            dealer_hand: self.dealer.hands[0].clone(),
-                            player_hands: self.player.hands.clone(),
-                            player_bankroll: self.player.bank_roll
-                        };
+                    // Check if dealer busted
+                    if self.dealer.hands[0].is_busted() {
+                        self.determine_winner_and_complete_round();
+                        return;
+                    }
+
+                    // Without a hole card, a blackjack on either side is settled as
+                    // soon as the dealer's second card is seen
+                    let player_natural = self.player.hands.len() == 1 && self.player.hands[0].is_natural_blackjack();
+                    if self.dealer.hands[0].is_natural_blackjack() || player_natural {
+                        self.determine_winner_and_complete_round();
+                        return;
                     }
-                } else {
-                    self.determine_winner_and_complete_round();
+
+                    // Continue dealer's turn
+                    self.state = GameState::DealerTurn {
+                        dealer_hand: self.dealer.hands[0].clone(),
+                        player_hands: self.player.hands.clone(),
+                        player_bankroll: self.player.bank_roll
+                    };
                 }
-            },
-            _ => {
+            } else {
+                self.determine_winner_and_complete_round();
             }
         }
     }
 
+    /// Marks the round as complete and records it in the history
+    ///
+    /// Side bets on the dealer's finished hand are settled first. If the round
+    /// ended before the dealer played, e.g. because every player hand busted,
+    /// the dealer draws out the hand for them anyway.
+    fn complete_round(&mut self) {
+        if self.side_bets.iter().any(|side_bet| side_bet.outcome.is_none() && side_bet.kind.settles_after_dealer()) {
+            while self.dealer_should_hit() {
+                match self.shoe.draw_card() {
+                    Some(card) => self.dealer.add_card_to_hand(card, 0),
+                    None => break,
+                }
+            }
+            self.settle_side_bets(true);
+        }
+        self.history.push_back(RoundRecord {
+            id: self.round_id,
+            session_id: self.session_id,
+            number: self.round,
+            dealer_hand: self.dealer.hands[0].clone(),
+            player_hands: self.player.hands.clone(),
+            side_bets: self.side_bets.clone(),
+            net: self.player.ledger.round_net(self.round_id),
+            player_bankroll: self.player.bank_roll,
+        });
+        while self.history.len() > self.settings.round_history_limit {
+            self.history.pop_front();
+        }
+
+        self.state = GameState::RoundComplete {
+            dealer_hand: self.dealer.hands[0].clone(),
+            player_hands: self.player.hands.clone(),
+            side_bets: self.side_bets.clone(),
+            player_bankroll: self.player.bank_roll
+        };
+    }
+
+    /// Clears the table for the next round, reshuffling once the cut card has come
+    /// out, or shuffling the discards straight back in with a continuous shuffler
     pub fn next_round(&mut self) {
-        self.player.reset_hands();
+        match self.settings.shuffle_mode {
+            ShuffleMode::Continuous { buffer } => self.shoe.shuffle_in_discards(buffer),
+            ShuffleMode::CutCard if self.shoe.penetration() >= self.settings.penetration => {
+                match self.settings.casino_shuffle {
+                    Some(procedure) => self.last_shuffle = Some(procedure.shuffle(&mut self.shoe)),
+                    None => self.shoe.reshuffle(),
+                }
+            }
+            ShuffleMode::CutCard => {}
+        }
+        self.round += 1;
+        self.round_id = Uuid::new_v4();
+        self.player.archive_hands(self.settings.round_history_limit);
         self.dealer.reset_hands();
+        self.side_bets.clear();
         self.state = GameState::WaitingForBet { player_bankroll: self.player.bank_roll }
     }
 
     pub fn determine_winner_and_complete_round(&mut self) {
         let dealer_hand = &self.dealer.hands[0];
         let dealer_value = dealer_hand.best_value();
-        for (_, hand) in self.player.hands.iter_mut().enumerate() {
+        let dealer_natural = dealer_hand.is_natural_blackjack();
+        let blackjack_payout = self.settings.rules.natural_payout();
+        let variant = self.settings.rules.variant;
+        let spanish = variant == GameVariant::Spanish21;
+        let dealer_22_pushes = variant.dealer_22_pushes() && dealer_value == 22;
+        // Only the player's real money is returned; a free bet just wins like it
+        let mut credits = Vec::new();
+        for hand in self.player.hands.iter_mut() {
+            // Surrendered hands and charlies are already settled
+            if matches!(hand.outcome, Some(HandOutcome::Surrender | HandOutcome::Win)) {
+                continue;
+            }
             let player_value = hand.best_value();
+            // Only a hand dealt as it stands can be a natural, not one split or switched into 21
+            let player_natural = hand.split_from.is_none() && !hand.switched && hand.is_natural_blackjack();
             let hand_outcome = if hand.is_busted() {
                 HandOutcome::Loss
+            } else if spanish && player_value == 21 && !player_natural {
+                // A Spanish 21 player 21 always wins, with a bonus for some hands
+                let winnings = match twenty_one_bonus(hand) {
+                    Some(bonus) => bonus.winnings(hand.bet, self.settings.payout_rounding),
+                    None => hand.bet,
+                };
+                credits.push((TransactionKind::Payout, hand.real_bet() + winnings));
+                HandOutcome::Win
+            } else if player_natural && dealer_natural && variant.dealer_wins_ties() && !variant.natural_beats_dealer_natural() {
+                HandOutcome::Loss
+            } else if player_natural && dealer_natural && !variant.natural_beats_dealer_natural() {
+                credits.push((TransactionKind::Refund, hand.real_bet()));
+                HandOutcome::Push
+            } else if player_natural {
+                let winnings = blackjack_payout.winnings(hand.bet, self.settings.payout_rounding);
+                credits.push((TransactionKind::Payout, hand.real_bet() + winnings));
+                HandOutcome::Blackjack
+            } else if dealer_natural {
+                // No hole card games lose doubles and splits to a dealer blackjack too
+                HandOutcome::Loss
+            } else if dealer_22_pushes {
+                credits.push((TransactionKind::Refund, hand.real_bet()));
+                HandOutcome::Push
             } else if dealer_hand.is_busted() {
-                self.player.bank_roll += hand.bet * 2f64;
+                credits.push((TransactionKind::Payout, hand.real_bet() + hand.bet));
                 HandOutcome::Win
             } else if dealer_value > player_value {
                 HandOutcome::Loss
             } else if player_value > dealer_value {
-                self.player.bank_roll += hand.bet * 2f64;
+                credits.push((TransactionKind::Payout, hand.real_bet() + hand.bet));
                 HandOutcome::Win
+            } else if variant.dealer_wins_ties() {
+                // Double Exposure and Pontoon ties go to the dealer
+                HandOutcome::Loss
             } else {
-                self.player.bank_roll += hand.bet;
+                credits.push((TransactionKind::Refund, hand.real_bet()));
                 HandOutcome::Push
             };
             hand.outcome = Option::from(hand_outcome);
         }
+        for (kind, amount) in credits {
+            self.player.credit(self.round_id, kind, amount);
+        }
 
-        self.state = GameState::RoundComplete {
-            dealer_hand: self.dealer.hands[0].clone(),
-            player_hands: self.player.hands.clone(),
-            player_bankroll: self.player.bank_roll
-        };
-        return;
+        self.complete_round();
     }
 }
 
@@ -311,28 +757,49 @@ pub enum PlayerType {
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameAction {
-    Hit, Stand, Double, Split
+    Hit, Stand, Double, Split, Surrender,
+    /// Blackjack Switch only: swap the second cards of the two hands before playing them
+    Switch,
 }
 
 impl GameAction {
     pub fn from_string(value: &str) -> Option<GameAction> {
         match value.to_lowercase().trim() {
-            "h" | "hit" => Some(Hit),
-            "s" | "stand" => Some(Stand),
-            "d" | "double" => Some(Double),
+            "h" | "hit" | "t" | "twist" => Some(Hit),
+            "s" | "stand" | "stick" => Some(Stand),
+            "d" | "double" | "b" | "buy" => Some(Double),
             "p" | "split" => Some(Split),
+            "r" | "surrender" => Some(Surrender),
+            "w" | "switch" => Some(Switch),
             _ => None
         }
     }
 
-    pub fn to_string(&self) -> String {
-        match self {
-            Hit => "HIT".to_string(),
-            Stand => "STAND".to_string(),
-            Double => "DOUBLE".to_string(),
-            Split => "SPLIT".to_string(),
+    /// The action's name at a table dealing `variant`: Pontoon players twist,
+    /// stick and buy rather than hit, stand and double
+    pub fn label(&self, variant: GameVariant) -> String {
+        match (variant, self) {
+            (GameVariant::Pontoon, Hit) => "TWIST".to_string(),
+            (GameVariant::Pontoon, Stand) => "STICK".to_string(),
+            (GameVariant::Pontoon, Double) => "BUY".to_string(),
+            _ => self.to_string(),
         }
     }
+
+}
+
+impl fmt::Display for GameAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Hit => "HIT",
+            Stand => "STAND",
+            Double => "DOUBLE",
+            Split => "SPLIT",
+            Surrender => "SURRENDER",
+            Switch => "SWITCH",
+        };
+        f.write_str(label)
+    }
 }
 
 pub enum HandResult {
@@ -341,26 +808,923 @@ pub enum HandResult {
 
 pub enum GameState {
     WaitingForBet {
-        player_bankroll: f64,
+        player_bankroll: Money,
     },
     WaitingToDeal {
-        player_bet: f64,
-        player_bankroll: f64,
+        player_bet: Money,
+        player_bankroll: Money,
     },
     PlayerTurn {
+        /// Every dealer card dealt so far, including the hole card
         dealer_hand: Hand,
+        /// The upcard is shown, except in Pontoon where both dealer cards stay face down
+        dealer_upcard_visible: bool,
+        /// The hole card may be shown; otherwise only the upcard is, until the dealer's turn
+        dealer_hole_card_visible: bool,
         player_hands: Vec<Hand>,
-        player_bankroll: f64,
+        player_bankroll: Money,
         active_hand_index: usize,
     },
     DealerTurn {
         dealer_hand: Hand,
         player_hands: Vec<Hand>,
-        player_bankroll: f64,
+        player_bankroll: Money,
     },
     RoundComplete {
         dealer_hand: Hand,
         player_hands: Vec<Hand>,
-        player_bankroll: f64,
+        /// Settled side wagers, each with its own outcome
+        side_bets: Vec<SideBet>,
+        player_bankroll: Money,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{parse_cards, Card};
+    use crate::game_settings::TablePreset;
+    use crate::money::Rounding;
+    use crate::rules::{DoubleRule, Payout};
+    use crate::scenario::Scenario;
+    use crate::shuffle::CasinoShuffle;
+    use crate::side_bet::{BusterBlackjackPays, PerfectPairsPays, TwentyOnePlusThreePays};
+
+    fn stacked_game(player: &str, dealer: &str, settings: GameSettings) -> Game {
+        Scenario::new()
+            .player(parse_cards(player).unwrap())
+            .dealer(parse_cards(dealer).unwrap())
+            .build_game(settings)
+            .unwrap()
+    }
+
+    /// Deals a $10 round, with any side bets, from a shoe stacked so the player
+    /// gets `hands`, the dealer `dealer` and the cards after that `then`
+    fn dealt_game(
+        settings: GameSettings,
+        hands: &[&str],
+        dealer: &str,
+        then: &str,
+        side_bets: &[(SideBetKind, Money)],
+    ) -> Game {
+        let mut scenario = Scenario::new()
+            .player(parse_cards(hands[0]).unwrap())
+            .dealer(parse_cards(dealer).unwrap())
+            .then(parse_cards(then).unwrap());
+        if let Some(second) = hands.get(1) {
+            scenario = scenario.second_hand(parse_cards(second).unwrap());
+        }
+        let mut game = scenario.build_game(settings).unwrap();
+        game.accept_user_bet_with_side_bets(Money::from_dollars(10), side_bets).unwrap();
+        game.deal_initial_cards();
+        game
+    }
+
+    /// Deals a $10 round at a preset table, see `dealt_game`
+    fn preset_game(preset: TablePreset, hands: &[&str], dealer: &str, then: &str) -> Game {
+        dealt_game(GameSettings::from_preset("Player".to_string(), preset), hands, dealer, then, &[])
+    }
+
+    /// Table that accepts bets in whole cents, for exercising payout rounding
+    fn cent_settings() -> GameSettings {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.bet_increment = Money::from_cents(1);
+        settings
+    }
+
+    #[test]
+    fn test_blackjack_pays_three_to_two_rounded_down() {
+        let mut game = stacked_game("As Kd", "9c 7h", cent_settings());
+        game.accept_user_bet(Money::from_cents(505)).unwrap();
+        game.deal_initial_cards();
+
+        // $5.05 back plus $7.575 rounded down to $7.57
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000) + Money::from_cents(757));
+    }
+
+    #[test]
+    fn test_blackjack_payout_rounding_is_configurable() {
+        let mut settings = cent_settings();
+        settings.payout_rounding = Rounding::Up;
+        let mut game = stacked_game("As Kd", "9c 7h", settings);
+        game.accept_user_bet(Money::from_cents(505)).unwrap();
+        game.deal_initial_cards();
+
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000) + Money::from_cents(758));
+    }
+
+    #[test]
+    fn test_win_pays_even_money() {
+        let mut game = stacked_game("Ts 9d", "9c 8h", cent_settings());
+        game.accept_user_bet(Money::from_cents(1234)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Stand, 0).unwrap();
+        game.next_dealer_turn();
+
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000) + Money::from_cents(1234));
+    }
+
+    #[test]
+    fn test_round_is_recorded_in_ledger() {
+        let mut game = Scenario::new()
+            .player(parse_cards("5s 6d").unwrap())
+            .dealer(parse_cards("9c 7h").unwrap())
+            .then(parse_cards("Kd 2c").unwrap())
+            .build_game(GameSettings::new("Player".to_string(), 6))
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Double, 0).unwrap();
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+
+        let kinds: Vec<TransactionKind> = game.player.ledger.round(game.round_id).iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![TransactionKind::Bet, TransactionKind::Double, TransactionKind::Payout]);
+        assert_eq!(game.player.ledger.round_net(game.round_id), Money::from_dollars(20));
+
+        game.next_round();
+        assert_eq!(game.round, 2);
+        let summary = game.player.ledger.summary();
+        assert_eq!(summary.total_wagered, Money::from_dollars(20));
+        assert_eq!(summary.peak, Money::from_dollars(10_020));
+    }
+
+    #[test]
+    fn test_completed_rounds_are_kept_in_history() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.round_history_limit = 2;
+        let mut game = Game::new(settings);
+        let mut round_ids = Vec::new();
+        for _ in 0..3 {
+            round_ids.push(game.round_id);
+            game.accept_user_bet(Money::from_dollars(10)).unwrap();
+            game.deal_initial_cards();
+            while let GameState::PlayerTurn { active_hand_index, .. } = *game.get_state() {
+                game.process_player_action(Stand, active_hand_index).unwrap();
+            }
+            while let GameState::DealerTurn { .. } = game.get_state() {
+                game.next_dealer_turn();
+            }
+            game.next_round();
+        }
+
+        assert_eq!(game.history.len(), 2);
+        assert!(game.find_round(round_ids[0]).is_none());
+        let record = game.find_round(round_ids[2]).unwrap();
+        assert_eq!(record.number, 3);
+        assert_eq!(record.session_id, game.session_id);
+        assert_eq!(record.net, game.player.ledger.round_net(round_ids[2]));
+
+        let hand_id = record.player_hands[0].id;
+        let (found_round, _) = game.find_hand(hand_id).unwrap();
+        assert_eq!(found_round.id, round_ids[2]);
+        assert_eq!(game.player.previous_hands.len(), 2);
+        assert_eq!(game.player.previous_hands.back().unwrap().id, hand_id);
+    }
+
+    #[test]
+    fn test_bust_on_last_split_hand_still_settles_first_hand() {
+        let mut game = Scenario::new()
+            .player(parse_cards("8s 8d").unwrap())
+            .dealer(parse_cards("Tc 6h").unwrap())
+            .then(parse_cards("Ts 3c 9d Kc 6c").unwrap())
+            .build_game(GameSettings::new("Player".to_string(), 6))
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Split, 0).unwrap();
+        // 8,T stands; 8,3 hits to 20 then busts with the K; dealer busts with the 6
+        game.process_player_action(Stand, 0).unwrap();
+        game.process_player_action(Hit, 1).unwrap();
+        assert!(matches!(game.get_state(), GameState::PlayerTurn { active_hand_index: 1, .. }));
+        game.process_player_action(Hit, 1).unwrap();
+        assert!(game.player.hands[1].is_busted());
+        assert!(matches!(game.get_state(), GameState::DealerTurn { .. }));
+
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.hands[1].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000));
+    }
+
+    #[test]
+    fn test_bet_outside_table_limits_is_rejected() {
+        let mut game = Game::new(GameSettings::new("Player".to_string(), 6));
+
+        assert!(game.accept_user_bet(Money::from_dollars(1)).is_err());
+        assert!(game.accept_user_bet(Money::from_dollars(6_000)).is_err());
+        assert!(game.accept_user_bet(Money::from_cents(1050)).is_err());
+        assert!(game.accept_user_bet(Money::ZERO).is_err());
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000));
+        assert!(matches!(game.get_state(), GameState::WaitingForBet { .. }));
+    }
+
+    #[test]
+    fn test_bet_larger_than_bankroll_is_rejected() {
+        let mut game = Game::new(GameSettings::new("Player".to_string(), 6));
+        game.player.bank_roll = Money::from_dollars(20);
+
+        assert_eq!(
+            game.accept_user_bet(Money::from_dollars(25)).unwrap_err(),
+            "You cannot bet more than you have"
+        );
+    }
+
+    #[test]
+    fn test_double_may_exceed_table_maximum() {
+        let mut game = stacked_game("5s 6d", "9c 7h", GameSettings::new("Player".to_string(), 6));
+        game.accept_user_bet(game.settings.max_bet).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Double, 0).unwrap();
+
+        assert_eq!(game.player.hands[0].bet, game.settings.max_bet * 2);
+    }
+
+    #[test]
+    fn test_double_and_split_require_bankroll() {
+        let mut game = stacked_game("8s 8d", "9c 7h", GameSettings::new("Player".to_string(), 6));
+        game.player.bank_roll = Money::from_dollars(150);
+        game.accept_user_bet(Money::from_dollars(100)).unwrap();
+        game.deal_initial_cards();
+
+        assert!(game.process_player_action(Double, 0).is_err());
+        assert!(game.process_player_action(Split, 0).is_err());
+        assert_eq!(game.player.hands.len(), 1);
+        assert_eq!(game.player.bank_roll, Money::from_dollars(50));
+    }
+
+    #[test]
+    fn test_split_requires_a_pair() {
+        let mut game = stacked_game("8s 9d", "9c 7h", GameSettings::new("Player".to_string(), 6));
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+
+        assert_eq!(game.process_player_action(Split, 0).unwrap_err(), "Only a pair can be split");
+    }
+
+    #[test]
+    fn test_dealer_soft_17_follows_rules() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        let mut game = Scenario::new()
+            .player(parse_cards("Ts 8d").unwrap())
+            .dealer(parse_cards("Ac 6h").unwrap())
+            .then(parse_cards("2c").unwrap())
+            .build_game(settings.clone())
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Stand, 0).unwrap();
+        game.next_dealer_turn();
+        assert_eq!(game.dealer.hands[0].cards.len(), 2);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+
+        settings.rules.dealer_hits_soft_17 = true;
+        let mut game = Scenario::new()
+            .player(parse_cards("Ts 8d").unwrap())
+            .dealer(parse_cards("Ac 6h").unwrap())
+            .then(parse_cards("2c").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Stand, 0).unwrap();
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+        assert_eq!(game.dealer.hands[0].best_value(), 19);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+    }
+
+    #[test]
+    fn test_blackjack_payout_follows_rules() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.rules.blackjack_payout = Payout::SIX_TO_FIVE;
+        let mut game = stacked_game("As Kd", "9c 7h", settings);
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_012));
+    }
+
+    #[test]
+    fn test_double_after_split_follows_rules() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.rules.double_after_split = false;
+        let mut game = Scenario::new()
+            .player(parse_cards("8s 8d").unwrap())
+            .dealer(parse_cards("6c Th").unwrap())
+            .then(parse_cards("3c").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Split, 0).unwrap();
+
+        assert_eq!(
+            game.process_player_action(Double, 0).unwrap_err(),
+            "Doubling after a split is not allowed at this table"
+        );
+    }
+
+    #[test]
+    fn test_kelly_advice_uses_bankroll_and_count() {
+        // Nine low cards out of six decks leave a Hi-Lo true count of about +1.5
+        let mut game = Scenario::new()
+            .player(parse_cards("2s 3d").unwrap())
+            .dealer(parse_cards("4c 5h").unwrap())
+            .then(parse_cards("6s 2d 3c 4d 5s").unwrap())
+            .build_game(GameSettings::new("Player".to_string(), 6))
+            .unwrap();
+        assert_eq!(game.kelly_advice().unwrap().full, Money::ZERO);
+
+        for _ in 0..9 {
+            game.shoe.draw_card();
+        }
+        let advice = game.kelly_advice().unwrap();
+        assert!(advice.full.is_positive());
+        assert!(advice.quarter <= advice.half && advice.half <= advice.full);
+
+        let bet = game.settings.largest_bet_up_to(advice.half).unwrap();
+        game.accept_user_bet(bet).unwrap();
+        assert!(game.kelly_advice().unwrap().full < advice.full);
+    }
+
+    #[test]
+    fn test_running_count_waits_for_the_hole_card() {
+        // The player sees 9, 8 and the dealer's 5; the hole king counts once revealed
+        let mut game = dealt_game(GameSettings::new("Player".to_string(), 1), &["9s 8d"], "5c Kh", "Tc", &[]);
+        assert_eq!(game.running_count(), 1);
+        game.process_player_action(Stand, 0).unwrap();
+        assert_eq!(game.running_count(), 0);
+
+        let game = preset_game(TablePreset::DoubleExposure, &["9s 8d"], "5c Kh", "Tc");
+        assert_eq!(game.running_count(), 0);
+        // Neither Pontoon dealer card is seen before the dealer's turn
+        let game = preset_game(TablePreset::Pontoon, &["9s 8d"], "5c 6h", "Tc");
+        assert_eq!(game.running_count(), 0);
+    }
+
+    #[test]
+    fn test_split_aces_follow_rules() {
+        let mut game = Scenario::new()
+            .player(parse_cards("As Ad").unwrap())
+            .dealer(parse_cards("9c 7h").unwrap())
+            .then(parse_cards("Ah 5c").unwrap())
+            .build_game(GameSettings::new("Player".to_string(), 6))
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Split, 0).unwrap();
+
+        assert_eq!(game.check_action(Split, 0).unwrap_err(), "Aces cannot be resplit at this table");
+        assert_eq!(game.check_action(Hit, 0).unwrap_err(), "Split aces receive one card only");
+        assert!(game.check_action(Double, 0).is_err());
+        assert!(game.check_action(Stand, 0).is_ok());
+
+        game.settings.rules.resplit_aces = true;
+        game.settings.rules.hit_split_aces = true;
+        assert!(game.check_action(Split, 0).is_ok());
+        assert!(game.check_action(Hit, 0).is_ok());
+    }
+
+    #[test]
+    fn test_split_hands_are_limited() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.rules.max_split_hands = 2;
+        let mut game = Scenario::new()
+            .player(parse_cards("8s 8d").unwrap())
+            .dealer(parse_cards("9c 7h").unwrap())
+            .then(parse_cards("8h").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Split, 0).unwrap();
+
+        assert_eq!(game.check_action(Split, 0).unwrap_err(), "You cannot split into more than 2 hands");
+    }
+
+    #[test]
+    fn test_double_restrictions_follow_rules() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.rules.double_on = DoubleRule::TenOrEleven;
+        let mut game = Scenario::new()
+            .player(parse_cards("5s 4d").unwrap())
+            .dealer(parse_cards("9c 7h").unwrap())
+            .then(parse_cards("2c").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+
+        assert_eq!(game.check_action(Double, 0).unwrap_err(), "This table only allows doubling on 10 or 11");
+        game.process_player_action(Hit, 0).unwrap();
+        assert_eq!(game.check_action(Double, 0).unwrap_err(), "You can only double on your first two cards");
+    }
+
+    #[test]
+    fn test_late_surrender_returns_half_the_bet() {
+        let mut game = stacked_game("Ts 6d", "Tc 7h", GameSettings::from_preset("Player".to_string(), TablePreset::AtlanticCity));
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Surrender, 0).unwrap();
+
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Surrender));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_995));
+        assert!(matches!(game.get_state(), GameState::RoundComplete { .. }));
+
+        let mut game = stacked_game("Ts 6d", "Tc 7h", GameSettings::new("Player".to_string(), 6));
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        assert_eq!(game.process_player_action(Surrender, 0).unwrap_err(), "Surrender is not offered at this table");
+    }
+
+    #[test]
+    fn test_no_hole_card_natural_waits_for_dealer() {
+        let settings = GameSettings::from_preset("Player".to_string(), TablePreset::EuropeanNoHoleCard);
+        let mut game = stacked_game("As Kd", "Tc Ah", settings);
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        assert_eq!(game.dealer.hands[0].cards.len(), 1);
+        assert!(matches!(game.get_state(), GameState::DealerTurn { .. }));
+
+        game.next_dealer_turn();
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Push));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000));
+    }
+
+    #[test]
+    fn test_no_hole_card_dealer_blackjack_takes_doubles() {
+        let settings = GameSettings::from_preset("Player".to_string(), TablePreset::EuropeanNoHoleCard);
+        let mut game = Scenario::new()
+            .player(parse_cards("5s 6d").unwrap())
+            .dealer(parse_cards("Tc Ah").unwrap())
+            .then(parse_cards("9s").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Double, 0).unwrap();
+        assert_eq!(game.player.hands[0].best_value(), 20);
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+
+        assert!(game.dealer.hands[0].is_natural_blackjack());
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_980));
+    }
+
+    fn perfect_pairs_settings() -> GameSettings {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.side_bets.perfect_pairs = Some(PerfectPairsPays::new());
+        settings
+    }
+
+    #[test]
+    fn test_perfect_pairs_settles_after_the_deal() {
+        let mut game = stacked_game("8s 8c", "Ac Kh", perfect_pairs_settings());
+        game.accept_user_bet_with_side_bets(Money::from_dollars(10), &[(SideBetKind::PerfectPairs, Money::from_dollars(5))]).unwrap();
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_985));
+        game.deal_initial_cards();
+
+        // The dealer blackjack takes the main bet, but the colored pair pays 12:1
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.side_bets[0].outcome, Some(SideBetOutcome::Win {
+            hand: "Colored pair".to_string(),
+            pays: Payout { numerator: 12, denominator: 1 },
+            winnings: Money::from_dollars(60),
+        }));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_050));
+        assert_eq!(game.history[0].side_bets, game.side_bets);
+
+        game.next_round();
+        assert!(game.side_bets.is_empty());
+    }
+
+    #[test]
+    fn test_losing_side_bet() {
+        let mut game = stacked_game("8s 9c", "7c 9h", perfect_pairs_settings());
+        game.accept_user_bet_with_side_bets(Money::from_dollars(10), &[(SideBetKind::PerfectPairs, Money::from_dollars(5))]).unwrap();
+        game.deal_initial_cards();
+
+        assert_eq!(game.side_bets[0].outcome, Some(SideBetOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_985));
+        let kinds: Vec<TransactionKind> = game.player.ledger.round(game.round_id).iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![TransactionKind::Bet, TransactionKind::SideBet]);
+    }
+
+    #[test]
+    fn test_side_bet_limits() {
+        let side_bet = |amount| [(SideBetKind::PerfectPairs, Money::from_dollars(amount))];
+        let mut game = Game::new(GameSettings::new("Player".to_string(), 6));
+        assert_eq!(
+            game.accept_user_bet_with_side_bets(Money::from_dollars(10), &side_bet(5)).unwrap_err(),
+            "Perfect Pairs is not offered at this table",
+        );
+
+        let mut game = Game::new(perfect_pairs_settings());
+        assert_eq!(
+            game.accept_user_bet_with_side_bets(Money::from_dollars(10), &side_bet(500)).unwrap_err(),
+            "The Perfect Pairs maximum is $100.00",
+        );
+        let twice = [side_bet(5)[0], side_bet(5)[0]];
+        assert!(game.accept_user_bet_with_side_bets(Money::from_dollars(10), &twice).is_err());
+        game.player.bank_roll = Money::from_dollars(12);
+        assert!(game.accept_user_bet_with_side_bets(Money::from_dollars(10), &side_bet(5)).is_err());
+        assert_eq!(game.player.bank_roll, Money::from_dollars(12));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_twenty_one_plus_three_uses_the_dealer_upcard() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.side_bets.twenty_one_plus_three = Some(TwentyOnePlusThreePays::new());
+        settings.side_bets.perfect_pairs = Some(PerfectPairsPays::new());
+        let mut game = stacked_game("9h Th", "Jh 7c", settings);
+        game.accept_user_bet_with_side_bets(Money::from_dollars(10), &[
+            (SideBetKind::TwentyOnePlusThree, Money::from_dollars(5)),
+            (SideBetKind::PerfectPairs, Money::from_dollars(5)),
+        ]).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Stand, 0).unwrap();
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+
+        // 9-10-J of hearts is a straight flush paying 40:1; the main hand wins too
+        let GameState::RoundComplete { side_bets, .. } = game.get_state() else {
+            panic!("Round should be complete");
+        };
+        assert_eq!(side_bets[0].to_string(), "21+3 $5.00: Straight flush pays 40:1 (+$200.00)");
+        assert_eq!(side_bets[1].outcome, Some(SideBetOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000 + 10 + 200 - 5));
+    }
+
+    fn buster_settings() -> GameSettings {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.side_bets.buster_blackjack = Some(BusterBlackjackPays::new());
+        settings
+    }
+
+    #[test]
+    fn test_dealer_completes_hand_for_buster_blackjack_after_player_busts() {
+        let mut game = dealt_game(buster_settings(), &["Ts 6d"], "Tc 6h", "9s 8c", &[(SideBetKind::BusterBlackjack, Money::from_dollars(5))]);
+        assert_eq!(game.side_bets[0].outcome, None);
+        game.process_player_action(Hit, 0).unwrap();
+
+        assert!(matches!(game.get_state(), GameState::RoundComplete { .. }));
+        assert_eq!(game.dealer.hands[0].cards.len(), 3);
+        assert_eq!(game.side_bets[0].outcome, Some(SideBetOutcome::Win {
+            hand: "Dealer busts with 3 cards".to_string(),
+            pays: Payout::EVEN_MONEY,
+            winnings: Money::from_dollars(5),
+        }));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_995));
+        assert_eq!(game.history[0].net, Money::from_dollars(-5));
+    }
+
+    #[test]
+    fn test_buster_blackjack_loses_when_dealer_stands() {
+        let mut game = dealt_game(buster_settings(), &["Ts 6d"], "Tc 6h", "4c", &[(SideBetKind::BusterBlackjack, Money::from_dollars(5))]);
+        game.process_player_action(Stand, 0).unwrap();
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+
+        assert_eq!(game.dealer.hands[0].best_value(), 20);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.side_bets[0].outcome, Some(SideBetOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_985));
+    }
+
+    fn play_out_dealer(game: &mut Game) {
+        while let GameState::DealerTurn { .. } = game.get_state() {
+            game.next_dealer_turn();
+        }
+    }
+
+    #[test]
+    fn test_spanish_21_player_21_always_wins() {
+        let mut game = preset_game(TablePreset::Spanish21, &["5s 6d"], "Jc 4h", "Kc 7s");
+        assert_eq!(game.shoe.cards_per_deck(), 48);
+        game.process_player_action(Hit, 0).unwrap();
+        play_out_dealer(&mut game);
+
+        assert_eq!(game.dealer.hands[0].best_value(), 21);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_010));
+    }
+
+    #[test]
+    fn test_spanish_21_five_card_bonus() {
+        let mut game = preset_game(TablePreset::Spanish21, &["2s 3d"], "Jc 8h", "4c 5h 7s");
+        for _ in 0..3 {
+            game.process_player_action(Hit, 0).unwrap();
+        }
+        play_out_dealer(&mut game);
+
+        // Five card 21 pays 3:2
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_015));
+    }
+
+    #[test]
+    fn test_spanish_21_late_double_and_rescue() {
+        let mut game = preset_game(TablePreset::Spanish21, &["2s 3d"], "Jc 8h", "4c 2h");
+        game.process_player_action(Hit, 0).unwrap();
+        game.process_player_action(Double, 0).unwrap();
+        assert_eq!(game.player.hands[0].best_value(), 11);
+        assert!(game.check_action(Hit, 0).is_err());
+
+        // Rescuing gives back the doubled portion and forfeits the original bet
+        game.process_player_action(Surrender, 0).unwrap();
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Surrender));
+        assert!(matches!(game.get_state(), GameState::RoundComplete { .. }));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+    }
+
+    #[test]
+    fn test_spanish_21_blackjack_beats_dealer_blackjack() {
+        let game = preset_game(TablePreset::Spanish21, &["As Kd"], "Ac Qh", "");
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Blackjack));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_015));
+    }
+
+    #[test]
+    fn test_spanish_21_shoe_has_no_tens() {
+        let settings = GameSettings::from_preset("Player".to_string(), TablePreset::Spanish21);
+        let scenario = Scenario::new().player(parse_cards("Ts 5d").unwrap()).dealer(parse_cards("9c").unwrap());
+        assert!(scenario.build_game(settings).is_err());
+    }
+
+    #[test]
+    fn test_blackjack_switch_swaps_second_cards() {
+        let mut game = preset_game(TablePreset::BlackjackSwitch, &["Ts 5d", "6c Th"], "9c 8h", "Kd");
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_980));
+        game.process_player_action(Switch, 0).unwrap();
+        assert_eq!(game.player.hands[0].best_value(), 20);
+        assert_eq!(game.player.hands[1].best_value(), 11);
+
+        game.process_player_action(Stand, 0).unwrap();
+        assert!(game.check_action(Switch, 1).is_err());
+        game.process_player_action(Double, 1).unwrap();
+        play_out_dealer(&mut game);
+
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.hands[1].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_030));
+    }
+
+    #[test]
+    fn test_blackjack_switch_dealer_22_pushes() {
+        let mut game = preset_game(TablePreset::BlackjackSwitch, &["Ts 9d", "As Kh"], "Tc 6h", "6d");
+        game.process_player_action(Stand, 0).unwrap();
+        game.process_player_action(Stand, 1).unwrap();
+        play_out_dealer(&mut game);
+
+        // The natural still wins, at even money
+        assert_eq!(game.dealer.hands[0].best_value(), 22);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Push));
+        assert_eq!(game.player.hands[1].outcome, Some(HandOutcome::Blackjack));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_010));
+    }
+
+    #[test]
+    fn test_switched_ace_ten_is_not_a_natural() {
+        let mut game = preset_game(TablePreset::BlackjackSwitch, &["As 9d", "7c Kh"], "Tc 6h", "6d");
+        game.process_player_action(Switch, 0).unwrap();
+        assert!(game.player.hands[0].is_natural_blackjack());
+        // Switching back would leave a dealt natural marked as switched
+        assert_eq!(game.check_action(Switch, 0).unwrap_err(), "The second cards have already been switched");
+        game.process_player_action(Stand, 0).unwrap();
+        game.process_player_action(Stand, 1).unwrap();
+        play_out_dealer(&mut game);
+
+        // A switched 21 is not protected from a dealer 22 like a natural
+        assert_eq!(game.dealer.hands[0].best_value(), 22);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Push));
+        assert_eq!(game.player.hands[1].outcome, Some(HandOutcome::Push));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000));
+
+        let mut game = preset_game(TablePreset::BlackjackSwitch, &["As 9d", "7c Kh"], "Tc 6h", "5d");
+        game.process_player_action(Switch, 0).unwrap();
+        game.process_player_action(Stand, 0).unwrap();
+        game.process_player_action(Stand, 1).unwrap();
+        play_out_dealer(&mut game);
+
+        // Nor does it beat a dealer 21 made with three cards
+        assert_eq!(game.dealer.hands[0].cards.len(), 3);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Push));
+        assert_eq!(game.player.hands[1].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+    }
+
+    #[test]
+    fn test_switch_only_at_blackjack_switch_tables() {
+        let mut game = stacked_game("Ts 5d", "9c 8h", GameSettings::new("Player".to_string(), 6));
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        assert_eq!(game.check_action(Switch, 0).unwrap_err(), "Switching is only offered at Blackjack Switch tables");
+
+        let mut game = preset_game(TablePreset::BlackjackSwitch, &["Ts 5d", "6c Th"], "9c 8h", "2s");
+        game.process_player_action(Hit, 0).unwrap();
+        assert_eq!(game.check_action(Switch, 0).unwrap_err(), "You can only switch before playing either hand");
+    }
+
+    #[test]
+    fn test_free_double_wins_on_house_money() {
+        let mut game = preset_game(TablePreset::FreeBet, &["6s 4d"], "9c 8h", "Kd");
+        assert!(game.is_free_action(Double, 0));
+        // A broke player can still take the free double
+        game.player.bank_roll = Money::ZERO;
+        game.process_player_action(Double, 0).unwrap();
+        assert_eq!(game.player.hands[0].bet, Money::from_dollars(20));
+        assert_eq!(game.player.hands[0].free_bet, Money::from_dollars(10));
+        play_out_dealer(&mut game);
+
+        // $10 stake back plus $20 won on the whole bet
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(30));
+    }
+
+    #[test]
+    fn test_free_split_loses_nothing_extra() {
+        let mut game = preset_game(TablePreset::FreeBet, &["8s 8d"], "Tc 9h", "2c 5h");
+        assert!(game.is_free_action(Split, 0));
+        game.process_player_action(Split, 0).unwrap();
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+        game.process_player_action(Stand, 0).unwrap();
+        game.process_player_action(Stand, 1).unwrap();
+        play_out_dealer(&mut game);
+
+        // Both hands lose to 19, but only the original $10 was the player's
+        assert_eq!(game.player.hands[1].free_bet, Money::from_dollars(10));
+        assert_eq!(game.player.hands[1].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+    }
+
+    #[test]
+    fn test_free_bet_dealer_22_pushes_and_tens_split_at_cost() {
+        let mut game = preset_game(TablePreset::FreeBet, &["Ts Td"], "Tc 6h", "6d");
+        assert!(!game.is_free_action(Split, 0));
+        assert!(!game.is_free_action(Double, 0));
+        game.process_player_action(Stand, 0).unwrap();
+        play_out_dealer(&mut game);
+
+        assert_eq!(game.dealer.hands[0].best_value(), 22);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Push));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_000));
+    }
+
+    #[test]
+    fn test_double_exposure_shows_hole_card_and_ties_lose() {
+        let mut game = preset_game(TablePreset::DoubleExposure, &["Ts 8d"], "9c 9h", "");
+        let GameState::PlayerTurn { dealer_hand, dealer_hole_card_visible, .. } = game.get_state() else {
+            panic!("Player should be on turn");
+        };
+        assert!(dealer_hole_card_visible);
+        assert_eq!(dealer_hand.best_value(), 18);
+
+        game.process_player_action(Stand, 0).unwrap();
+        play_out_dealer(&mut game);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+    }
+
+    #[test]
+    fn test_double_exposure_blackjack_wins_ties_at_even_money() {
+        let game = preset_game(TablePreset::DoubleExposure, &["As Kd"], "Ac Qh", "");
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Blackjack));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_010));
+    }
+
+    #[test]
+    fn test_classic_game_keeps_hole_card_hidden() {
+        let mut game = stacked_game("Ts 8d", "9c 9h", GameSettings::new("Player".to_string(), 6));
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        assert!(matches!(game.get_state(), GameState::PlayerTurn { dealer_hole_card_visible: false, .. }));
+    }
+
+    #[test]
+    fn test_five_card_charlie_wins_at_once() {
+        let mut settings = GameSettings::new("Player".to_string(), 6);
+        settings.rules.charlie = Some(5);
+        let mut game = Scenario::new()
+            .player(parse_cards("2s 3d").unwrap())
+            .dealer(parse_cards("Tc Th").unwrap())
+            .then(parse_cards("2c 3h 4s").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        for _ in 0..2 {
+            game.process_player_action(Hit, 0).unwrap();
+            assert!(matches!(game.get_state(), GameState::PlayerTurn { .. }));
+        }
+        game.process_player_action(Hit, 0).unwrap();
+
+        // 14 in five cards beats the dealer's 20
+        assert_eq!(game.player.hands[0].best_value(), 14);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert!(matches!(game.get_state(), GameState::RoundComplete { .. }));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_010));
+    }
+
+    #[test]
+    fn test_pontoon_twist_to_fifteen_and_dealer_wins_ties() {
+        let mut game = preset_game(TablePreset::Pontoon, &["Ts 4d"], "9c 9h", "4s");
+        assert!(matches!(
+            game.get_state(),
+            GameState::PlayerTurn { dealer_upcard_visible: false, dealer_hole_card_visible: false, .. }
+        ));
+        let stick = GameAction::from_string("stick").unwrap();
+        assert_eq!(stick.label(GameVariant::Pontoon), "STICK");
+        assert_eq!(game.process_player_action(stick, 0).unwrap_err(), "You cannot stick below 15 in Pontoon");
+
+        game.process_player_action(GameAction::from_string("twist").unwrap(), 0).unwrap();
+        game.process_player_action(stick, 0).unwrap();
+        play_out_dealer(&mut game);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+    }
+
+    #[test]
+    fn test_pontoon_five_card_trick_pays_two_to_one() {
+        let mut game = preset_game(TablePreset::Pontoon, &["2s 3d"], "Tc Th", "2c 3h 4s");
+        for _ in 0..3 {
+            game.process_player_action(Hit, 0).unwrap();
+        }
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Win));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(10_020));
+    }
+
+    #[test]
+    fn test_pontoon_dealer_pontoon_beats_player_pontoon() {
+        let game = preset_game(TablePreset::Pontoon, &["As Kd"], "Ac Qh", "");
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_990));
+    }
+
+    #[test]
+    fn test_pontoon_buy_keeps_the_hand_in_play() {
+        let mut game = preset_game(TablePreset::Pontoon, &["5s 4d"], "9c 9h", "3c 4h");
+        let buy = GameAction::from_string("buy").unwrap();
+        assert_eq!(buy.label(GameVariant::Pontoon), "BUY");
+        game.process_player_action(buy, 0).unwrap();
+        assert_eq!(game.player.hands[0].bet, Money::from_dollars(20));
+
+        // The bought hand is on 12, so it must twist, and may not buy again
+        assert!(matches!(game.get_state(), GameState::PlayerTurn { active_hand_index: 0, .. }));
+        assert_eq!(game.check_action(Stand, 0).unwrap_err(), "You cannot stick below 15 in Pontoon");
+        game.process_player_action(Hit, 0).unwrap();
+        assert!(game.check_action(buy, 0).is_err());
+        game.process_player_action(Stand, 0).unwrap();
+        play_out_dealer(&mut game);
+
+        assert_eq!(game.player.hands[0].best_value(), 16);
+        assert_eq!(game.player.hands[0].outcome, Some(HandOutcome::Loss));
+        assert_eq!(game.player.bank_roll, Money::from_dollars(9_980));
+    }
+
+    #[test]
+    fn test_continuous_shuffler_returns_discards_every_round() {
+        let mut settings = GameSettings::new("Player".to_string(), 1);
+        settings.shuffle_mode = ShuffleMode::Continuous { buffer: 2 };
+        let mut game = Scenario::new()
+            .player(parse_cards("Ts 9d").unwrap())
+            .dealer(parse_cards("Kc 8h").unwrap())
+            .build_game(settings)
+            .unwrap();
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(Stand, 0).unwrap();
+        play_out_dealer(&mut game);
+        assert_eq!(game.running_count(), -2);
+
+        // Only the last two cards dealt, the player's 9 and the dealer's 8, are
+        // still out of the shoe, so the two tens no longer count
+        game.next_round();
+        assert_eq!(game.shoe.cards.len(), 50);
+        assert_eq!(game.shoe.discarded, parse_cards("9d 8h").unwrap());
+        assert_eq!(game.running_count(), 0);
+    }
+
+    #[test]
+    fn test_casino_shuffle_at_cut_card_is_traced() {
+        let mut settings = GameSettings::new("Player".to_string(), 1);
+        settings.casino_shuffle = Some(CasinoShuffle::new());
+        let mut game = Game::new(settings);
+        let dealt: Vec<Card> = (0..40).filter_map(|_| game.shoe.draw_card()).collect();
+
+        game.next_round();
+        let trace = game.last_shuffle.as_ref().unwrap();
+        assert_eq!(game.shoe.cards.len(), 52);
+        let first = trace.slug(0..1)[0];
+        assert_eq!(game.shoe.cards[51 - first], dealt[0]);
+    }
+}
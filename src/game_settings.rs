@@ -1,9 +1,54 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use crate::counting::CountingSystem;
+use crate::money::{chip_breakdown, ChipBreakdown, Money, Rounding};
+use crate::house_edge::{house_edge, HouseEdge};
+use crate::rules::{DoubleRule, GameVariant, Payout, Rules};
+use crate::shoe::ShuffleMode;
+use crate::shuffle::CasinoShuffle;
+use crate::side_bet::{SideBetKind, SideBetRules};
+
+/// Table configuration
+///
+/// Settings missing from a config file take their value from
+/// `GameSettings::default`.
 #[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct GameSettings {
     /// Name of the main player
     pub player_name: String,
     /// Number of decks to use in the shoe
     pub deck_count: u8,
+    /// How fractional-cent payouts (e.g. 3:2 on an odd bet) are rounded
+    pub payout_rounding: Rounding,
+    /// Smallest bet accepted at the table
+    pub min_bet: Money,
+    /// Largest initial bet accepted at the table (doubles and splits may exceed it)
+    pub max_bet: Money,
+    /// Bets must be a whole multiple of this amount
+    pub bet_increment: Money,
+    /// Chip values available at the table, used to display bets and payouts
+    pub chip_denominations: Vec<Money>,
+    /// Number of completed rounds kept in the game's history for review, and of
+    /// hands kept in the player's previous hands
+    pub round_history_limit: usize,
+    /// Counting system used for running and true counts at this table
+    pub counting_system: CountingSystem,
+    /// Fraction of the shoe dealt before the cut card brings on a reshuffle
+    pub penetration: f64,
+    /// Reshuffle at the cut card, or shuffle the discards back in after every
+    /// round with a continuous shuffling machine
+    pub shuffle_mode: ShuffleMode,
+    /// Shuffle the shoe at the cut card the way a dealer does by hand, for
+    /// shuffle tracking practice; `None` for a perfectly random shuffle. Only
+    /// allowed with the cut card, as a continuous shuffler never reaches it
+    pub casino_shuffle: Option<CasinoShuffle>,
+    /// Playing rules such as soft 17, doubling after a split and the blackjack payout
+    pub rules: Rules,
+    /// Side bets offered at the table and their pay tables
+    pub side_bets: SideBetRules,
 }
 
 impl GameSettings {
@@ -32,6 +77,18 @@ impl GameSettings {
         Self {
             player_name,
             deck_count,
+            payout_rounding: Rounding::Down,
+            min_bet: Money::from_dollars(5),
+            max_bet: Money::from_dollars(5_000),
+            bet_increment: Money::from_dollars(1),
+            chip_denominations: default_chip_denominations(),
+            round_history_limit: 100,
+            counting_system: CountingSystem::HiLo,
+            penetration: 0.75,
+            shuffle_mode: ShuffleMode::CutCard,
+            casino_shuffle: None,
+            rules: Rules::new(),
+            side_bets: SideBetRules::new(),
         }
     }
 
@@ -46,7 +103,119 @@ impl GameSettings {
         Self {
             player_name,
             deck_count: 6,
+            payout_rounding: Rounding::Down,
+            min_bet: Money::from_dollars(5),
+            max_bet: Money::from_dollars(5_000),
+            bet_increment: Money::from_dollars(1),
+            chip_denominations: default_chip_denominations(),
+            round_history_limit: 100,
+            counting_system: CountingSystem::HiLo,
+            penetration: 0.75,
+            shuffle_mode: ShuffleMode::CutCard,
+            casino_shuffle: None,
+            rules: Rules::new(),
+            side_bets: SideBetRules::new(),
+        }
+    }
+
+    /// Creates a configuration with the deck count and rules of a well known table
+    ///
+    /// Everything else uses the same defaults as `new`, and any field can be
+    /// changed afterwards to customize the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blackjack_engine::game_settings::{GameSettings, TablePreset};
+    ///
+    /// let mut settings = GameSettings::from_preset("Alice".to_string(), TablePreset::AtlanticCity);
+    /// settings.rules.late_surrender = false;
+    /// assert_eq!(settings.deck_count, 8);
+    /// assert!(settings.validate().is_ok());
+    /// ```
+    pub fn from_preset(player_name: String, preset: TablePreset) -> Self {
+        let mut settings = Self::new(player_name, preset.deck_count());
+        settings.rules = preset.rules();
+        settings
+    }
+
+    /// Differences in deck count and rules between this table and `other`,
+    /// as "name: ours vs theirs"
+    pub fn compare(&self, other: &GameSettings) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.deck_count != other.deck_count {
+            differences.push(format!("Decks: {} vs {}", self.deck_count, other.deck_count));
+        }
+        differences.extend(self.rules.differences(&other.rules));
+        differences
+    }
+
+    /// Checks an initial bet against the table minimum, maximum and increment
+    ///
+    /// # Returns
+    /// `Ok(())` if the bet is allowed, `Err` with description if not
+    pub fn check_bet(&self, bet: Money) -> Result<(), String> {
+        if bet < self.min_bet {
+            return Err(format!("The table minimum is {}", self.min_bet));
+        }
+        if bet > self.max_bet {
+            return Err(format!("The table maximum is {}", self.max_bet));
+        }
+        self.check_increment(bet)
+    }
+
+    /// Checks a side wager against the table's side bets and their limit
+    ///
+    /// # Returns
+    /// `Ok(())` if the side bet is allowed, `Err` with description if not
+    pub fn check_side_bet(&self, kind: SideBetKind, amount: Money) -> Result<(), String> {
+        if !self.side_bets.offers(kind) {
+            return Err(format!("{} is not offered at this table", kind));
+        }
+        if !amount.is_positive() {
+            return Err("Side bets must be greater than zero".to_string());
         }
+        if amount > self.side_bets.max_bet {
+            return Err(format!("The {} maximum is {}", kind, self.side_bets.max_bet));
+        }
+        self.check_increment(amount)
+    }
+
+    /// Checks a wager is a whole number of bet increments
+    ///
+    /// Settings are not validated until a game is configured, so a zero
+    /// increment is reported here rather than divided by.
+    fn check_increment(&self, amount: Money) -> Result<(), String> {
+        if !self.bet_increment.is_positive() {
+            return Err("Bet increment must be greater than zero".to_string());
+        }
+        if amount.cents() % self.bet_increment.cents() != 0 {
+            return Err(format!("Bets must be in multiples of {}", self.bet_increment));
+        }
+        Ok(())
+    }
+
+    /// Largest bet allowed at this table that does not exceed `amount`
+    ///
+    /// # Returns
+    /// `None` if `amount` is below the table minimum or the bet increment is not positive
+    pub fn largest_bet_up_to(&self, amount: Money) -> Option<Money> {
+        if !self.bet_increment.is_positive() {
+            return None;
+        }
+        let increment = self.bet_increment.cents();
+        let bet = Money::from_cents(amount.min(self.max_bet).cents().div_euclid(increment) * increment);
+        (bet >= self.min_bet).then_some(bet)
+    }
+
+    /// Basic strategy house edge of this table's rules and deck count, if known
+    pub fn house_edge(&self) -> Option<HouseEdge> {
+        house_edge(&self.rules, self.deck_count as usize)
+    }
+
+    /// Breaks an amount into this table's chip denominations for display
+    pub fn chip_breakdown(&self, amount: Money) -> ChipBreakdown {
+        chip_breakdown(amount, &self.chip_denominations)
     }
 
     /// Validates if the settings are within acceptable ranges
@@ -54,26 +223,208 @@ impl GameSettings {
     /// # Returns
     /// `Ok(())` if settings are valid, `Err` with description if invalid
     pub fn validate(&self) -> Result<(), String> {
+        self.validate_with_key().map_err(|(_, message)| message)
+    }
+
+    /// Like `validate`, but also names the setting at fault, e.g. `rules.max_split_hands`,
+    /// so config file errors can point at the offending key
+    pub fn validate_with_key(&self) -> Result<(), (String, String)> {
+        let invalid = |key: &str, message: &str| Err((key.to_string(), message.to_string()));
         if self.player_name.trim().is_empty() {
-            return Err("Player name cannot be empty".to_string());
+            return invalid("player_name", "Player name cannot be empty");
         }
         if !(1..=8).contains(&self.deck_count) {
-            return Err("Deck count must be between 1 and 8".to_string());
+            return invalid("deck_count", "Deck count must be between 1 and 8");
         }
-        Ok(())
+        if !self.min_bet.is_positive() {
+            return invalid("min_bet", "Minimum bet must be greater than zero");
+        }
+        if self.max_bet < self.min_bet {
+            return invalid("max_bet", "Maximum bet cannot be less than the minimum bet");
+        }
+        if !self.bet_increment.is_positive() {
+            return invalid("bet_increment", "Bet increment must be greater than zero");
+        }
+        if self.min_bet.cents() % self.bet_increment.cents() != 0 {
+            return invalid("min_bet", "Minimum bet must be a multiple of the bet increment");
+        }
+        if !(self.penetration > 0f64 && self.penetration <= 1f64) {
+            return invalid("penetration", "Penetration must be greater than 0 and at most 1");
+        }
+        if let ShuffleMode::Continuous { buffer } = self.shuffle_mode {
+            let shoe_size = self.deck_count as usize * self.rules.variant.cards_per_deck();
+            if buffer > shoe_size / 2 {
+                return invalid("shuffle_mode", "A continuous shuffler can hold out at most half the shoe");
+            }
+        }
+        if let Some(procedure) = &self.casino_shuffle {
+            if matches!(self.shuffle_mode, ShuffleMode::Continuous { .. }) {
+                return invalid("casino_shuffle", "A continuous shuffler is never shuffled by hand");
+            }
+            procedure.validate_with_key().map_err(|(key, message)| (format!("casino_shuffle.{}", key), message))?;
+        }
+        if self.chip_denominations.iter().any(|chip| !chip.is_positive()) {
+            return invalid("chip_denominations", "Chip denominations must be greater than zero");
+        }
+        self.rules.validate_with_key().map_err(|(key, message)| (format!("rules.{}", key), message))?;
+        self.side_bets.validate_with_key().map_err(|(key, message)| (format!("side_bets.{}", key), message))
+    }
+}
+
+impl Default for GameSettings {
+    /// Six decks for a player called "Player"
+    fn default() -> Self {
+        Self::default_single_player("Player".to_string())
     }
 }
 
+/// Deck counts and rules of well known tables, as a starting point for a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter)]
+pub enum TablePreset {
+    /// Six decks, S17, double after split, resplit aces, late surrender
+    LasVegasStrip,
+    /// Two decks, H17, double after split
+    DowntownDoubleDeck,
+    /// Eight decks, S17, double after split, late surrender
+    AtlanticCity,
+    /// Six decks, S17, no hole card, double on 9-11, no resplitting
+    EuropeanNoHoleCard,
+    /// One deck, H17, no double after split, blackjack pays 6:5
+    SingleDeckSixToFive,
+    /// Six decks, S17, no hole card, double on 9-11 including after a split
+    UkMacau,
+    /// Six Spanish decks, H17, double after split, resplit and hit split aces, late surrender
+    Spanish21,
+    /// Six decks, H17, double after split, two hands that may switch cards
+    BlackjackSwitch,
+    /// Six decks, H17, double after split, free doubles and splits
+    FreeBet,
+    /// Six decks, H17, both dealer cards face up, double on 9-11, no resplitting
+    DoubleExposure,
+    /// Six decks, H17, double after split, twist and stick with both dealer cards face down
+    Pontoon,
+}
+
+impl TablePreset {
+    pub fn deck_count(&self) -> u8 {
+        match self {
+            TablePreset::LasVegasStrip => 6,
+            TablePreset::DowntownDoubleDeck => 2,
+            TablePreset::AtlanticCity => 8,
+            TablePreset::EuropeanNoHoleCard => 6,
+            TablePreset::SingleDeckSixToFive => 1,
+            TablePreset::UkMacau => 6,
+            TablePreset::Spanish21 => 6,
+            TablePreset::BlackjackSwitch => 6,
+            TablePreset::FreeBet => 6,
+            TablePreset::DoubleExposure => 6,
+            TablePreset::Pontoon => 6,
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        match self {
+            TablePreset::LasVegasStrip => Rules {
+                resplit_aces: true,
+                late_surrender: true,
+                ..Rules::new()
+            },
+            TablePreset::DowntownDoubleDeck => Rules {
+                dealer_hits_soft_17: true,
+                ..Rules::new()
+            },
+            TablePreset::AtlanticCity => Rules {
+                late_surrender: true,
+                ..Rules::new()
+            },
+            TablePreset::EuropeanNoHoleCard => Rules {
+                double_on: DoubleRule::NineToEleven,
+                double_after_split: false,
+                max_split_hands: 2,
+                no_hole_card: true,
+                ..Rules::new()
+            },
+            TablePreset::SingleDeckSixToFive => Rules {
+                dealer_hits_soft_17: true,
+                double_after_split: false,
+                blackjack_payout: Payout::SIX_TO_FIVE,
+                ..Rules::new()
+            },
+            TablePreset::UkMacau => Rules {
+                double_on: DoubleRule::NineToEleven,
+                no_hole_card: true,
+                ..Rules::new()
+            },
+            TablePreset::Spanish21 => Rules {
+                variant: GameVariant::Spanish21,
+                dealer_hits_soft_17: true,
+                resplit_aces: true,
+                hit_split_aces: true,
+                late_surrender: true,
+                ..Rules::new()
+            },
+            TablePreset::BlackjackSwitch => Rules {
+                variant: GameVariant::BlackjackSwitch,
+                dealer_hits_soft_17: true,
+                ..Rules::new()
+            },
+            TablePreset::FreeBet => Rules {
+                variant: GameVariant::FreeBet,
+                dealer_hits_soft_17: true,
+                ..Rules::new()
+            },
+            TablePreset::DoubleExposure => Rules {
+                variant: GameVariant::DoubleExposure,
+                dealer_hits_soft_17: true,
+                double_on: DoubleRule::NineToEleven,
+                max_split_hands: 2,
+                ..Rules::new()
+            },
+            TablePreset::Pontoon => Rules {
+                variant: GameVariant::Pontoon,
+                dealer_hits_soft_17: true,
+                ..Rules::new()
+            },
+        }
+    }
+}
+
+impl fmt::Display for TablePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TablePreset::LasVegasStrip => "Las Vegas Strip",
+            TablePreset::DowntownDoubleDeck => "Downtown double deck",
+            TablePreset::AtlanticCity => "Atlantic City",
+            TablePreset::EuropeanNoHoleCard => "European no hole card",
+            TablePreset::SingleDeckSixToFive => "6:5 single deck",
+            TablePreset::UkMacau => "UK / Macau",
+            TablePreset::Spanish21 => "Spanish 21",
+            TablePreset::BlackjackSwitch => "Blackjack Switch",
+            TablePreset::FreeBet => "Free Bet",
+            TablePreset::DoubleExposure => "Double Exposure",
+            TablePreset::Pontoon => "Pontoon",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The usual casino chip set: $1, $5, $25, $100, $500, $1,000 and $5,000
+pub fn default_chip_denominations() -> Vec<Money> {
+    [1, 5, 25, 100, 500, 1_000, 5_000].into_iter().map(Money::from_dollars).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use strum::IntoEnumIterator;
+    use crate::side_bet::PerfectPairsPays;
 
     #[test]
     fn test_new_game_settings() {
         let settings = GameSettings::new(
             "Player1".to_string(),
             6,
-            1,
         );
         assert_eq!(settings.player_name, "Player1");
         assert_eq!(settings.deck_count, 6);
@@ -121,6 +472,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_table_limits() {
+        let mut settings = GameSettings::new("Player1".to_string(), 6);
+        settings.max_bet = Money::from_dollars(1);
+        assert_eq!(
+            settings.validate().unwrap_err(),
+            "Maximum bet cannot be less than the minimum bet"
+        );
+
+        let mut settings = GameSettings::new("Player1".to_string(), 6);
+        settings.bet_increment = Money::from_dollars(2);
+        assert_eq!(
+            settings.validate().unwrap_err(),
+            "Minimum bet must be a multiple of the bet increment"
+        );
+    }
+
+    #[test]
+    fn test_check_bet() {
+        let settings = GameSettings::new("Player1".to_string(), 6);
+        assert!(settings.check_bet(Money::from_dollars(5)).is_ok());
+        assert!(settings.check_bet(Money::from_dollars(5_000)).is_ok());
+        assert_eq!(settings.check_bet(Money::from_dollars(4)).unwrap_err(), "The table minimum is $5.00");
+        assert_eq!(settings.check_bet(Money::from_dollars(5_001)).unwrap_err(), "The table maximum is $5000.00");
+        assert_eq!(settings.check_bet(Money::from_cents(550)).unwrap_err(), "Bets must be in multiples of $1.00");
+    }
+
+    #[test]
+    fn test_largest_bet_up_to() {
+        let settings = GameSettings::new("Player1".to_string(), 6);
+        assert_eq!(settings.largest_bet_up_to(Money::from_cents(8_799)), Some(Money::from_dollars(87)));
+        assert_eq!(settings.largest_bet_up_to(Money::from_dollars(9_000)), Some(Money::from_dollars(5_000)));
+        assert_eq!(settings.largest_bet_up_to(Money::from_dollars(4)), None);
+    }
+
+    #[test]
+    fn test_zero_increment_is_reported_not_divided_by() {
+        let mut settings = GameSettings::new("Player1".to_string(), 6);
+        settings.bet_increment = Money::ZERO;
+        settings.side_bets.perfect_pairs = Some(PerfectPairsPays::new());
+
+        assert_eq!(settings.check_bet(Money::from_dollars(10)).unwrap_err(), "Bet increment must be greater than zero");
+        assert_eq!(
+            settings.check_side_bet(SideBetKind::PerfectPairs, Money::from_dollars(5)).unwrap_err(),
+            "Bet increment must be greater than zero"
+        );
+        assert_eq!(settings.largest_bet_up_to(Money::from_dollars(10)), None);
+    }
+
+    #[test]
+    fn test_chip_breakdown() {
+        let settings = GameSettings::new("Player1".to_string(), 6);
+        let breakdown = settings.chip_breakdown(Money::from_dollars(136));
+        assert_eq!(breakdown.chips, vec![
+            (Money::from_dollars(100), 1),
+            (Money::from_dollars(25), 1),
+            (Money::from_dollars(5), 2),
+            (Money::from_dollars(1), 1),
+        ]);
+        assert_eq!(breakdown.remainder, Money::ZERO);
+    }
+
     #[test]
     fn test_settings_clone_and_equality() {
         let settings1 = GameSettings::new(
@@ -130,4 +543,40 @@ mod tests {
         let settings2 = settings1.clone();
         assert_eq!(settings1, settings2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_presets_are_consistent() {
+        for preset in TablePreset::iter() {
+            let settings = GameSettings::from_preset("Player1".to_string(), preset);
+            assert!(settings.validate().is_ok(), "{}", preset);
+            assert_eq!(settings.deck_count, preset.deck_count());
+        }
+    }
+
+    #[test]
+    fn test_preset_house_edges() {
+        let edge = |preset| GameSettings::from_preset("Player1".to_string(), preset).house_edge().unwrap().total();
+        // Published figures: Strip 6D S17 DAS LS RSA 0.26%, Atlantic City 8D S17 DAS LS 0.35%
+        assert!((edge(TablePreset::LasVegasStrip) - 0.0026).abs() < 0.0005);
+        assert!((edge(TablePreset::AtlanticCity) - 0.0035).abs() < 0.0005);
+        assert!(edge(TablePreset::SingleDeckSixToFive) > 0.01);
+        assert!(edge(TablePreset::EuropeanNoHoleCard) > edge(TablePreset::LasVegasStrip));
+    }
+
+    #[test]
+    fn test_compare_presets() {
+        let strip = GameSettings::from_preset("Player1".to_string(), TablePreset::LasVegasStrip);
+        let atlantic_city = GameSettings::from_preset("Player1".to_string(), TablePreset::AtlanticCity);
+        assert_eq!(strip.compare(&atlantic_city), vec![
+            "Decks: 6 vs 8".to_string(),
+            "Resplit aces: yes vs no".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_customized_preset_is_validated() {
+        let mut settings = GameSettings::from_preset("Player1".to_string(), TablePreset::EuropeanNoHoleCard);
+        settings.rules.late_surrender = true;
+        assert!(settings.validate().is_err());
+    }
+}
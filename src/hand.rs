@@ -1,17 +1,32 @@
+use std::fmt;
+use std::str::FromStr;
 use strum_macros::EnumIter;
-use crate::card::{Card, Rank};
-use crate::game::GameAction;
-use crate::game::GameAction::{Double, Hit, Split, Stand};
+use crate::card::{parse_cards, Card, CardStyle, Rank};
+use crate::money::Money;
+use uuid::Uuid;
 
 /// Represents a player's hand in a blackjack game
 #[derive(Clone, Debug)]
 pub struct Hand {
+    /// Unique id, kept when the hand is cloned into game state or round history
+    pub id: Uuid,
     /// Current bet amount for this hand
-    pub bet: f64,
+    pub bet: Money,
     /// Cards in the hand
     pub cards: Vec<Card>,
     /// How the hand turned out
-    pub outcome: Option<HandOutcome>
+    pub outcome: Option<HandOutcome>,
+    /// The bet was doubled, so the hand takes no more cards
+    pub doubled: bool,
+    /// Part of `bet` put up by the house through Free Bet doubles and splits. It
+    /// wins like the rest of the bet, but is never returned to the player
+    pub free_bet: Money,
+    /// Id of the hand dealt at the start of the round that this hand was split
+    /// from, or `None` if it was never split. Split hands cannot be naturals.
+    pub split_from: Option<Uuid>,
+    /// The hand's second card was swapped in from the other hand by a Blackjack
+    /// Switch. Switched hands cannot be naturals either.
+    pub switched: bool,
 }
 
 #[derive(Debug, Eq, Hash, PartialEq, Clone)]
@@ -20,17 +35,27 @@ pub enum HandOutcome {
     Win,
     Loss,
     Push,
-    Blackjack
+    Blackjack,
+    /// Gave up the hand for half the bet back
+    Surrender
 }
 
-impl HandOutcome {
-    pub fn to_string(&self) -> String {
-        match self {
-            HandOutcome::Win => "WIN".to_string(),
-            HandOutcome::Loss => "LOSS".to_string(),
-            HandOutcome::Push => "PUSH".to_string(),
-            HandOutcome::Blackjack => "BLACKJACK".to_string(),
-        }
+impl fmt::Display for HandOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            HandOutcome::Win => "WIN",
+            HandOutcome::Loss => "LOSS",
+            HandOutcome::Push => "PUSH",
+            HandOutcome::Blackjack => "BLACKJACK",
+            HandOutcome::Surrender => "SURRENDER",
+        };
+        f.write_str(label)
+    }
+}
+
+impl Default for Hand {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -38,34 +63,54 @@ impl Hand {
     /// Creates a new empty hand with the default bet of 100
     pub fn new() -> Self {
         Self {
-            bet: 100f64,
+            id: Uuid::new_v4(),
+            bet: Money::from_dollars(100),
             cards: Vec::new(),
-            outcome: None
+            outcome: None,
+            doubled: false,
+            split_from: None,
+            free_bet: Money::ZERO,
+            switched: false,
         }
     }
 
     /// Creates a new hand with a specific bet amount
-    pub fn with_bet(bet: f64) -> Self {
+    pub fn with_bet(bet: Money) -> Self {
         Self {
+            id: Uuid::new_v4(),
             bet,
             cards: Vec::new(),
-            outcome: None
+            outcome: None,
+            doubled: false,
+            split_from: None,
+            free_bet: Money::ZERO,
+            switched: false,
         }
     }
 
     pub fn with_card(card: Card) -> Self {
         Self {
-            bet: 0f64,
+            id: Uuid::new_v4(),
+            bet: Money::ZERO,
             cards: vec![card],
-            outcome: None
+            outcome: None,
+            doubled: false,
+            split_from: None,
+            free_bet: Money::ZERO,
+            switched: false,
         }
     }
 
-    pub fn with_card_and_bet(card: Card, bet: f64) -> Self {
+    pub fn with_card_and_bet(card: Card, bet: Money) -> Self {
         Self {
+            id: Uuid::new_v4(),
             bet,
             cards: vec![card],
-            outcome: None
+            outcome: None,
+            doubled: false,
+            split_from: None,
+            free_bet: Money::ZERO,
+            switched: false,
         }
     }
 
@@ -79,10 +124,15 @@ impl Hand {
         self.cards.len() == 2 && self.cards[0].rank == self.cards[1].rank
     }
 
+    /// The player's own money riding on the hand: `bet` less any free bet
+    pub fn real_bet(&self) -> Money {
+        self.bet - self.free_bet
+    }
+
     /// Doubles the current bet amount
-    /// Returns Ok(()) if successful, or Err if doubling would cause overflow
     pub fn double_bet(&mut self) {
-        self.bet = self.bet * 2f64;
+        self.bet *= 2;
+        self.doubled = true;
     }
 
     /// Calculates all possible hand values, accounting for aces
@@ -138,13 +188,40 @@ impl Hand {
         self.possible_values().iter().all(|&v| v > 21)
     }
 
-    pub fn to_string(&self) -> String {
-        let mut hand_as_string = String::from("");
+    /// Returns true if the hand's best total counts an ace as 11
+    pub fn is_soft(&self) -> bool {
+        let hard_total: u32 = self.cards.iter().map(|card| card.rank.value()[0] as u32).sum();
+        self.cards.iter().any(|card| card.rank == Rank::Ace) && hard_total + 10 == self.best_value()
+    }
+}
+
+impl Hand {
+    /// Renders the cards in the given style, separated by spaces
+    pub fn render(&self, style: CardStyle) -> String {
+        self.cards.iter()
+            .map(|card| card.render(style))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl FromStr for Hand {
+    type Err = String;
+
+    /// Parses a hand with the default bet from card notation, e.g. "As Kd" or "A,T,6"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hand = Hand::new();
+        hand.cards = parse_cards(s)?;
+        Ok(hand)
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for card in self.cards.iter() {
-            hand_as_string.push_str(&*card.to_string());
-            hand_as_string.push(' ');
+            write!(f, "{} ", card)?;
         }
-        hand_as_string
+        Ok(())
     }
 }
 
@@ -156,14 +233,21 @@ mod tests {
     #[test]
     fn test_new_hand() {
         let hand = Hand::new();
-        assert_eq!(hand.bet, 100f64);
+        assert_eq!(hand.bet, Money::from_dollars(100));
         assert!(hand.cards.is_empty());
     }
 
     #[test]
     fn test_with_bet() {
-        let hand = Hand::with_bet(200f64);
-        assert_eq!(hand.bet, 200f64);
+        let hand = Hand::with_bet(Money::from_dollars(200));
+        assert_eq!(hand.bet, Money::from_dollars(200));
+    }
+
+    #[test]
+    fn test_hands_have_unique_ids() {
+        let hand = Hand::new();
+        assert_ne!(hand.id, Hand::new().id);
+        assert_eq!(hand.id, hand.clone().id);
     }
 
     #[test]
@@ -199,6 +283,15 @@ mod tests {
         assert!(hand.is_blackjack());
     }
 
+    #[test]
+    fn test_parse_hand() {
+        let hand: Hand = "A,T,6".parse().unwrap();
+        assert_eq!(hand.cards.len(), 3);
+        assert_eq!(hand.best_value(), 17);
+        assert_eq!(hand.render(CardStyle::Ascii), "As Ts 6s");
+        assert!("A,Z".parse::<Hand>().is_err());
+    }
+
     #[test]
     fn test_bust() {
         let mut hand = Hand::new();
@@ -207,4 +300,11 @@ mod tests {
         hand.add_card(Card::new(Rank::Jack, Suit::Diamonds));
         assert!(hand.is_busted());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_soft_totals() {
+        assert!("A,6".parse::<Hand>().unwrap().is_soft());
+        assert!(!"A,6,T".parse::<Hand>().unwrap().is_soft());
+        assert!(!"T,7".parse::<Hand>().unwrap().is_soft());
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt;
+use crate::rules::{DoubleRule, GameVariant, Rules};
+
+/// House edge of an eight-deck game where the dealer stands on soft 17 and
+/// peeks for blackjack, any two cards may be doubled but not after a split,
+/// pairs split to four hands, split aces take one card, there is no surrender
+/// and blackjack pays 3:2
+pub const BASE_HOUSE_EDGE: f64 = 0.0057;
+
+/// How much one rule moves the house edge away from the base game
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleEffect {
+    pub rule: String,
+    /// Change in house edge as a fraction of the initial bet; negative favours the player
+    pub effect: f64,
+}
+
+/// Basic strategy house edge for a rule set, broken down rule by rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct HouseEdge {
+    /// House edge of the base game, see `BASE_HOUSE_EDGE`
+    pub base: f64,
+    /// Every rule that differs from the base game, in the order they were applied
+    pub effects: Vec<RuleEffect>,
+}
+
+impl HouseEdge {
+    /// House edge as a fraction of the initial bet; negative when the player has the edge
+    pub fn total(&self) -> f64 {
+        self.base + self.effects.iter().map(|effect| effect.effect).sum::<f64>()
+    }
+
+    fn add(&mut self, rule: impl Into<String>, effect: f64) {
+        if effect != 0f64 {
+            self.effects.push(RuleEffect { rule: rule.into(), effect });
+        }
+    }
+}
+
+impl fmt::Display for HouseEdge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<32}{:>+7.2}%", "Base game", self.base * 100f64)?;
+        for effect in self.effects.iter() {
+            writeln!(f, "{:<32}{:>+7.2}%", effect.rule, effect.effect * 100f64)?;
+        }
+        write!(f, "{:<32}{:>+7.2}%", "House edge", self.total() * 100f64)
+    }
+}
+
+/// Estimates the basic strategy house edge for `rules` dealt from `deck_count` decks
+///
+/// Adds the published effect of each rule to the base game. Rule effects are
+/// measured one at a time, so unusual combinations can drift a few hundredths
+/// of a percent from an exact calculation. Each variant is a single offset
+/// calibrated so that one common rule set lands on its published figure; other
+/// rule sets only hold as well as the classic rule effects carry over.
+///
+/// # Returns
+/// `None` for Pontoon, which has no published figure to calibrate against
+///
+/// # Examples
+///
+/// ```
+/// use blackjack_engine::house_edge::house_edge;
+/// use blackjack_engine::rules::Rules;
+///
+/// let edge = house_edge(&Rules::new(), 6).unwrap();
+/// assert!((edge.total() - 0.0041).abs() < 0.0001);
+/// ```
+pub fn house_edge(rules: &Rules, deck_count: usize) -> Option<HouseEdge> {
+    if rules.variant == GameVariant::Pontoon {
+        return None;
+    }
+    let mut edge = HouseEdge {
+        base: BASE_HOUSE_EDGE,
+        effects: Vec::new(),
+    };
+
+    let decks = match deck_count {
+        0 | 1 => -0.0048,
+        2 => -0.0019,
+        3 => -0.0010,
+        4 => -0.0006,
+        5 => -0.0003,
+        6 => -0.0002,
+        7 => -0.0001,
+        _ => 0f64,
+    };
+    edge.add(format!("{} deck{}", deck_count, if deck_count == 1 { "" } else { "s" }), decks);
+
+    if rules.dealer_hits_soft_17 {
+        edge.add("Dealer hits soft 17", if deck_count <= 2 { 0.0019 } else { 0.0022 });
+    }
+    if rules.double_after_split {
+        edge.add("Double after split", -0.0014);
+    }
+    match rules.double_on {
+        DoubleRule::AnyTwoCards => {}
+        DoubleRule::NineToEleven => edge.add("Double on 9-11 only", 0.0009),
+        DoubleRule::TenOrEleven => edge.add("Double on 10-11 only", 0.0018),
+    }
+    match rules.max_split_hands {
+        0..=2 => edge.add("No resplitting", 0.0005),
+        3 => edge.add("Split to 3 hands", 0.0001),
+        _ => {}
+    }
+    if rules.resplit_aces {
+        edge.add("Resplit aces", -0.0008);
+    }
+    if rules.hit_split_aces {
+        edge.add("Hit split aces", -0.0019);
+    }
+    if rules.late_surrender {
+        edge.add("Late surrender", if rules.dealer_hits_soft_17 { -0.0009 } else { -0.0008 });
+    }
+    if rules.no_hole_card {
+        edge.add("No hole card", 0.0011);
+    }
+    match rules.charlie {
+        Some(5) => edge.add("5 card charlie", -0.0146),
+        Some(6) => edge.add("6 card charlie", -0.0016),
+        Some(7) => edge.add("7 card charlie", -0.0001),
+        _ => {}
+    }
+    // A player natural that the dealer does not tie comes up about 4.6% of the time
+    let payout = rules.natural_payout();
+    edge.add(format!("Blackjack pays {}", payout), (1.5 - payout.ratio()) * 0.0462);
+
+    if rules.variant == GameVariant::Spanish21 {
+        // Removing the tens costs about 2.2%; the bonuses, late doubling, rescue and
+        // player 21 always winning give most of it back. Calibrated so that six decks
+        // land on the Wizard of Odds figures of 0.40% (S17) and 0.76% (H17), which assume
+        // doubling after splits and on any number of cards with rescue, resplitting
+        // and hitting split aces, late surrender, a player natural beating a dealer
+        // natural and a player 21 always winning. The 21 bonuses are 3:2 for five
+        // cards, 2:1 for six and 3:1 for seven or more, and 3:2 for a mixed 6-7-8 or
+        // 7-7-7, 2:1 suited and 3:1 in spades; the suited 7-7-7 jackpot is left out
+        edge.add("Spanish 21 deck and bonuses", if rules.dealer_hits_soft_17 { 0.0049 } else { 0.0035 });
+    }
+    if rules.variant == GameVariant::BlackjackSwitch {
+        // Switching is worth far more than the 1:1 natural and the dealer 22 push
+        // cost. Calibrated offset, not computed: six decks H17 with double after
+        // split land on the Wizard of Odds figure of 0.58%
+        edge.add("Switching, dealer 22 pushes", -0.0236);
+    }
+    if rules.variant == GameVariant::FreeBet {
+        // The dealer 22 push costs more than the free doubles and splits give back.
+        // Calibrated offset, not computed: six decks H17 with double after split
+        // land on the Wizard of Odds figure of 1.02%
+        edge.add("Free doubles and splits, dealer 22 pushes", 0.0039);
+    }
+    if rules.variant == GameVariant::DoubleExposure {
+        // Seeing the hole card is worth more than the 1:1 natural and losing ties
+        // cost. Calibrated offset, not computed: six decks H17, double on 9-11 and no
+        // resplitting land on the Wizard of Odds figure of 0.69%
+        edge.add("Both dealer cards face up, ties lose", -0.0239);
+    }
+
+    Some(edge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Payout;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.0005
+    }
+
+    #[test]
+    fn test_published_house_edges() {
+        // Six decks, S17, DAS: 0.40%
+        assert!(close(house_edge(&Rules::new(), 6).unwrap().total(), 0.0040));
+        // Six decks, H17, DAS: 0.62%
+        let h17 = Rules { dealer_hits_soft_17: true, ..Rules::new() };
+        assert!(close(house_edge(&h17, 6).unwrap().total(), 0.0062));
+        // Eight decks, S17, DAS: 0.43%
+        assert!(close(house_edge(&Rules::new(), 8).unwrap().total(), 0.0043));
+        // Single deck, S17, no DAS is close to break even
+        let single = Rules { double_after_split: false, ..Rules::new() };
+        assert!(house_edge(&single, 1).unwrap().total().abs() < 0.001);
+    }
+
+    #[test]
+    fn test_six_to_five_costs_about_one_and_a_third_percent() {
+        let six_to_five = Rules { blackjack_payout: Payout::SIX_TO_FIVE, ..Rules::new() };
+        let cost = house_edge(&six_to_five, 6).unwrap().total() - house_edge(&Rules::new(), 6).unwrap().total();
+        assert!(close(cost, 0.0139));
+    }
+
+    #[test]
+    fn test_breakdown_lists_rules_that_differ_from_the_base_game() {
+        let rules = Rules {
+            double_on: DoubleRule::TenOrEleven,
+            hit_split_aces: true,
+            ..Rules::new()
+        };
+        let edge = house_edge(&rules, 6).unwrap();
+        let names: Vec<&str> = edge.effects.iter().map(|effect| effect.rule.as_str()).collect();
+        assert_eq!(names, vec!["6 decks", "Double after split", "Double on 10-11 only", "Hit split aces"]);
+        assert!(close(edge.total(), 0.0057 - 0.0002 - 0.0014 + 0.0018 - 0.0019));
+        assert!(edge.to_string().ends_with("House edge                        +0.40%"));
+    }
+
+    #[test]
+    fn test_spanish_21_calibration() {
+        // Pins the offsets to the rule sets they were calibrated against
+        let rules = Rules {
+            variant: GameVariant::Spanish21,
+            dealer_hits_soft_17: true,
+            resplit_aces: true,
+            hit_split_aces: true,
+            late_surrender: true,
+            ..Rules::new()
+        };
+        assert!(close(house_edge(&rules, 6).unwrap().total(), 0.0076));
+        let s17 = Rules { dealer_hits_soft_17: false, ..rules };
+        assert!(close(house_edge(&s17, 6).unwrap().total(), 0.0040));
+    }
+
+    #[test]
+    fn test_blackjack_switch_calibration() {
+        // Pins the offset to the rule set it was calibrated against
+        let rules = Rules { variant: GameVariant::BlackjackSwitch, dealer_hits_soft_17: true, ..Rules::new() };
+        let edge = house_edge(&rules, 6).unwrap();
+        assert!(close(edge.total(), 0.0058));
+        assert!(edge.effects.iter().any(|effect| effect.rule == "Blackjack pays 1:1"));
+    }
+
+    #[test]
+    fn test_free_bet_calibration() {
+        // Pins the offset to the rule set it was calibrated against
+        let rules = Rules { variant: GameVariant::FreeBet, dealer_hits_soft_17: true, ..Rules::new() };
+        assert!(close(house_edge(&rules, 6).unwrap().total(), 0.0102));
+    }
+
+    #[test]
+    fn test_double_exposure_calibration() {
+        // Pins the offset to the rule set it was calibrated against
+        let rules = Rules {
+            variant: GameVariant::DoubleExposure,
+            dealer_hits_soft_17: true,
+            double_on: DoubleRule::NineToEleven,
+            max_split_hands: 2,
+            ..Rules::new()
+        };
+        assert!(close(house_edge(&rules, 6).unwrap().total(), 0.0069));
+    }
+
+    #[test]
+    fn test_charlie_rules_favour_the_player() {
+        let five = Rules { charlie: Some(5), ..Rules::new() };
+        let six = Rules { charlie: Some(6), ..Rules::new() };
+        let base = house_edge(&Rules::new(), 6).unwrap().total();
+        assert!(close(base - house_edge(&five, 6).unwrap().total(), 0.0146));
+        assert!(house_edge(&six, 6).unwrap().total() > house_edge(&five, 6).unwrap().total());
+        assert!(house_edge(&six, 6).unwrap().total() < base);
+    }
+
+    #[test]
+    fn test_pontoon_has_no_estimate() {
+        let rules = Rules { variant: GameVariant::Pontoon, ..Rules::new() };
+        assert!(house_edge(&rules, 6).is_none());
+        assert!(rules.player_edge(6).is_none());
+    }
+
+    #[test]
+    fn test_player_edge_is_negated_house_edge() {
+        let rules = Rules::new();
+        assert_eq!(rules.player_edge(2), Some(-house_edge(&rules, 2).unwrap().total()));
+        assert!(rules.player_edge(1) > rules.player_edge(8));
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use crate::money::Money;
+use uuid::Uuid;
+
+/// What a bankroll movement was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionKind {
+    /// Initial wager placed before the deal
+    Bet,
+    /// Additional wager placed when doubling down
+    Double,
+    /// Additional wager placed for a new hand when splitting
+    Split,
+    /// Insurance side wager against a dealer blackjack
+    Insurance,
+    /// Optional side wager such as Perfect Pairs, placed with the initial bet
+    SideBet,
+    /// Stake plus winnings returned on a winning hand
+    Payout,
+    /// Stake returned on a push
+    Refund,
+}
+
+impl TransactionKind {
+    /// Returns true for money the player puts on the table
+    pub fn is_wager(&self) -> bool {
+        matches!(self, TransactionKind::Bet | TransactionKind::Double
+            | TransactionKind::Split | TransactionKind::Insurance | TransactionKind::SideBet)
+    }
+}
+
+impl fmt::Display for TransactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TransactionKind::Bet => "BET",
+            TransactionKind::Double => "DOUBLE",
+            TransactionKind::Split => "SPLIT",
+            TransactionKind::Insurance => "INSURANCE",
+            TransactionKind::SideBet => "SIDE BET",
+            TransactionKind::Payout => "PAYOUT",
+            TransactionKind::Refund => "REFUND",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A single debit or credit against the player's bankroll
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    /// Id of the round the transaction belongs to
+    pub round_id: Uuid,
+    pub kind: TransactionKind,
+    /// Signed amount: negative for debits, positive for credits
+    pub amount: Money,
+    /// Bankroll after the transaction was applied
+    pub balance: Money,
+}
+
+/// Totals for a playing session built from the ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// Number of rounds with at least one transaction
+    pub rounds: usize,
+    /// Sum of every bet, double, split, insurance and side wager
+    pub total_wagered: Money,
+    /// Overall result of the session
+    pub net_win: Money,
+    /// Best single round result (zero if no round was won)
+    pub biggest_win: Money,
+    /// Worst single round result as a positive amount (zero if no round was lost)
+    pub biggest_loss: Money,
+    /// Highest bankroll at the end of any round, including the opening balance
+    pub peak: Money,
+    /// Lowest bankroll at the end of any round, including the opening balance
+    pub trough: Money,
+}
+
+/// Record of every bankroll movement in a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ledger {
+    /// Bankroll at the start of the session
+    pub opening_balance: Money,
+    entries: Vec<Transaction>,
+}
+
+impl Ledger {
+    /// Creates an empty ledger for a session starting with `opening_balance`
+    pub fn new(opening_balance: Money) -> Self {
+        Self {
+            opening_balance,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Appends a transaction. `amount` is negative for debits.
+    pub fn record(&mut self, round_id: Uuid, kind: TransactionKind, amount: Money, balance: Money) {
+        self.entries.push(Transaction { round_id, kind, amount, balance });
+    }
+
+    /// All transactions in the order they happened
+    pub fn entries(&self) -> &[Transaction] {
+        &self.entries
+    }
+
+    /// Transactions belonging to a single round
+    pub fn round(&self, round_id: Uuid) -> Vec<&Transaction> {
+        self.entries.iter().filter(|t| t.round_id == round_id).collect()
+    }
+
+    /// Net result of a single round
+    pub fn round_net(&self, round_id: Uuid) -> Money {
+        self.entries.iter()
+            .filter(|t| t.round_id == round_id)
+            .map(|t| t.amount)
+            .sum()
+    }
+
+    /// Net result of each round, in the order the rounds were played
+    pub fn round_results(&self) -> Vec<(Uuid, Money)> {
+        let mut results: Vec<(Uuid, Money)> = Vec::new();
+        for transaction in &self.entries {
+            match results.last_mut() {
+                Some((round_id, net)) if *round_id == transaction.round_id => *net += transaction.amount,
+                _ => results.push((transaction.round_id, transaction.amount)),
+            }
+        }
+        results
+    }
+
+    /// Builds the session totals from the recorded transactions
+    pub fn summary(&self) -> SessionSummary {
+        let results = self.round_results();
+        let mut balance = self.opening_balance;
+        let mut peak = balance;
+        let mut trough = balance;
+        for (_, net) in results.iter() {
+            balance += *net;
+            peak = peak.max(balance);
+            trough = trough.min(balance);
+        }
+
+        SessionSummary {
+            rounds: results.len(),
+            total_wagered: self.entries.iter()
+                .filter(|t| t.kind.is_wager())
+                .map(|t| -t.amount)
+                .sum(),
+            net_win: results.iter().map(|(_, net)| *net).sum(),
+            biggest_win: results.iter().map(|(_, net)| *net).max().unwrap_or_default().max(Money::ZERO),
+            biggest_loss: -results.iter().map(|(_, net)| *net).min().unwrap_or_default().min(Money::ZERO),
+            peak,
+            trough,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dollars(amount: i64) -> Money {
+        Money::from_dollars(amount)
+    }
+
+    fn sample_ledger(rounds: &[Uuid; 3]) -> Ledger {
+        let mut ledger = Ledger::new(dollars(100));
+        // Round 1: bet 10, double 10, win
+        ledger.record(rounds[0], TransactionKind::Bet, dollars(-10), dollars(90));
+        ledger.record(rounds[0], TransactionKind::Double, dollars(-10), dollars(80));
+        ledger.record(rounds[0], TransactionKind::Payout, dollars(40), dollars(120));
+        // Round 2: bet 25, split 25, lose both
+        ledger.record(rounds[1], TransactionKind::Bet, dollars(-25), dollars(95));
+        ledger.record(rounds[1], TransactionKind::Split, dollars(-25), dollars(70));
+        // Round 3: bet 10, push
+        ledger.record(rounds[2], TransactionKind::Bet, dollars(-10), dollars(60));
+        ledger.record(rounds[2], TransactionKind::Refund, dollars(10), dollars(70));
+        ledger
+    }
+
+    fn round_ids() -> [Uuid; 3] {
+        [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()]
+    }
+
+    #[test]
+    fn test_round_net() {
+        let rounds = round_ids();
+        let ledger = sample_ledger(&rounds);
+        assert_eq!(ledger.round_net(rounds[0]), dollars(20));
+        assert_eq!(ledger.round_net(rounds[1]), dollars(-50));
+        assert_eq!(ledger.round_net(rounds[2]), Money::ZERO);
+        assert_eq!(ledger.round(rounds[1]).len(), 2);
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = sample_ledger(&round_ids()).summary();
+        assert_eq!(summary.rounds, 3);
+        assert_eq!(summary.total_wagered, dollars(80));
+        assert_eq!(summary.net_win, dollars(-30));
+        assert_eq!(summary.biggest_win, dollars(20));
+        assert_eq!(summary.biggest_loss, dollars(50));
+        assert_eq!(summary.peak, dollars(120));
+        assert_eq!(summary.trough, dollars(70));
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        let summary = Ledger::new(dollars(100)).summary();
+        assert_eq!(summary.rounds, 0);
+        assert_eq!(summary.net_win, Money::ZERO);
+        assert_eq!(summary.biggest_loss, Money::ZERO);
+        assert_eq!(summary.peak, dollars(100));
+        assert_eq!(summary.trough, dollars(100));
+    }
+}
@@ -1,9 +1,24 @@
 pub mod card;
+pub mod money;
 pub mod shoe;
+pub mod shuffle;
 pub mod player;
+pub mod ledger;
 pub mod hand;
+pub mod rules;
+pub mod house_edge;
+pub mod side_bet;
+pub mod spanish21;
 pub mod game_settings;
 pub mod game;
+pub mod counting;
+pub mod count_drill;
+pub mod true_count_drill;
+pub mod strategy;
+pub mod betting;
+pub mod analytics;
+pub mod scenario;
+pub mod config;
 
 pub fn add_one(left: u64, right: u64) -> u64 {
     left + right
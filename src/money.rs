@@ -0,0 +1,414 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An amount of money stored as a whole number of cents
+///
+/// Bets, bankrolls and payouts all use `Money` so that long simulations never
+/// accumulate floating point error. Fractional payouts (3:2 on an odd bet, for
+/// example) go through `mul_ratio` with an explicit `Rounding` rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money(i64);
+
+/// How a payout that lands on a fraction of a cent is rounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// Round towards negative infinity, in the house's favour (the usual casino rule)
+    #[default]
+    Down,
+    /// Round towards positive infinity, in the player's favour
+    Up,
+    /// Round to the nearest cent, with halves rounded up
+    Nearest,
+}
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    /// Creates an amount from a number of cents
+    pub const fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    /// Creates an amount from a whole number of dollars
+    pub const fn from_dollars(dollars: i64) -> Self {
+        Money(dollars * 100)
+    }
+
+    /// Returns the amount in cents
+    pub fn cents(&self) -> i64 {
+        self.0
+    }
+
+    /// Returns the amount in dollars as a float, for statistics and display only
+    pub fn as_dollars(&self) -> f64 {
+        self.0 as f64 / 100f64
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    /// Multiplies by `numerator / denominator`, rounding any fractional cent
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use blackjack_engine::money::{Money, Rounding};
+    ///
+    /// // 3:2 on a $5.05 bet is $7.575
+    /// let bet = Money::from_cents(505);
+    /// assert_eq!(bet.mul_ratio(3, 2, Rounding::Down), Money::from_cents(757));
+    /// assert_eq!(bet.mul_ratio(3, 2, Rounding::Up), Money::from_cents(758));
+    /// ```
+    pub fn mul_ratio(self, numerator: i64, denominator: i64, rounding: Rounding) -> Money {
+        let (numerator, denominator) = if denominator < 0 {
+            (-(numerator as i128), -(denominator as i128))
+        } else {
+            (numerator as i128, denominator as i128)
+        };
+        let product = self.0 as i128 * numerator;
+        let cents = match rounding {
+            Rounding::Down => product.div_euclid(denominator),
+            Rounding::Up => -(-product).div_euclid(denominator),
+            Rounding::Nearest => (2 * product + denominator).div_euclid(2 * denominator),
+        };
+        Money(cents as i64)
+    }
+
+    pub fn abs(self) -> Money {
+        Money(self.0.abs())
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: i64) -> Money {
+        Money(self.0 * rhs)
+    }
+}
+
+impl MulAssign<i64> for Money {
+    fn mul_assign(&mut self, rhs: i64) {
+        self.0 *= rhs;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, amount| total + amount)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        write!(f, "{}${}.{:02}", sign, cents / 100, cents % 100)
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    /// Parses amounts like "25", "12.5", "$7.50" or "-3.25"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid amount '{}'", s);
+        let value = s.trim();
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let value = value.strip_prefix('$').unwrap_or(value);
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+
+        if whole.is_empty() || fraction.len() > 2
+            || !whole.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let dollars: i64 = whole.parse().map_err(|_| invalid())?;
+        let cents: i64 = format!("{:0<2}", fraction).parse().map_err(|_| invalid())?;
+        let amount = dollars.checked_mul(100)
+            .and_then(|c| c.checked_add(cents))
+            .ok_or_else(invalid)?;
+        Ok(Money(if negative { -amount } else { amount }))
+    }
+}
+
+/// Written as a string such as "$12.50" so config files stay readable
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Reads either a string such as "$12.50" or a plain number of dollars
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MoneyVisitor;
+
+        impl de::Visitor<'_> for MoneyVisitor {
+            type Value = Money;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an amount such as \"$12.50\" or a number of dollars")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Money, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Money, E> {
+                value.checked_mul(100).map(Money).ok_or_else(|| E::custom("amount is too large"))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Money, E> {
+                i64::try_from(value).map_err(E::custom).and_then(|value| self.visit_i64(value))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Money, E> {
+                let cents = value * 100f64;
+                if !cents.is_finite() || cents.abs() > i64::MAX as f64 {
+                    return Err(E::custom("amount is too large"));
+                }
+                Ok(Money(cents.round() as i64))
+            }
+        }
+
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+/// An amount broken into casino chips for display
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipBreakdown {
+    /// (denomination, count) pairs, largest denomination first
+    pub chips: Vec<(Money, u32)>,
+    /// Whatever could not be made from the available chips, e.g. coins on a 3:2 payout
+    pub remainder: Money,
+}
+
+impl ChipBreakdown {
+    /// Total number of chips in the breakdown
+    pub fn chip_count(&self) -> u32 {
+        self.chips.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Breaks `amount` into the given chip denominations
+///
+/// Anything above twice the largest chip is paid in that chip; the rest uses the
+/// fewest chips possible, so non-standard sets such as $2.50 and $1 chips still
+/// produce exact stacks. Any part that cannot be made is left in `remainder`.
+pub fn chip_breakdown(amount: Money, denominations: &[Money]) -> ChipBreakdown {
+    let mut values: Vec<i64> = denominations.iter()
+        .map(Money::cents)
+        .filter(|cents| *cents > 0)
+        .collect();
+    values.sort_unstable_by(|a, b| b.cmp(a));
+    values.dedup();
+
+    if values.is_empty() || !amount.is_positive() {
+        return ChipBreakdown { chips: Vec::new(), remainder: amount };
+    }
+
+    let largest = values[0];
+    let target = amount.cents();
+    let tail = if target > 2 * largest { target % largest + largest } else { target };
+    let bulk = (target - tail) / largest;
+
+    // Fewest-chips table over multiples of the common divisor of the chip values
+    let unit = values.iter().fold(0, |a, &b| gcd(a, b));
+    let units = (tail / unit) as usize;
+    let mut fewest: Vec<Option<u32>> = vec![None; units + 1];
+    let mut last_chip: Vec<usize> = vec![0; units + 1];
+    fewest[0] = Some(0);
+    for total in 1..=units {
+        for (index, value) in values.iter().enumerate() {
+            let step = (*value / unit) as usize;
+            if step > total {
+                continue;
+            }
+            if let Some(count) = fewest[total - step] {
+                if fewest[total].is_none_or(|best| count + 1 < best) {
+                    fewest[total] = Some(count + 1);
+                    last_chip[total] = index;
+                }
+            }
+        }
+    }
+
+    let mut total = (0..=units).rev().find(|&t| fewest[t].is_some()).unwrap_or(0);
+    let remainder = tail - total as i64 * unit;
+    let mut counts = vec![0u32; values.len()];
+    counts[0] = bulk as u32;
+    while total > 0 {
+        let index = last_chip[total];
+        counts[index] += 1;
+        total -= (values[index] / unit) as usize;
+    }
+
+    ChipBreakdown {
+        chips: values.into_iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(value, count)| (Money(value), count))
+            .collect(),
+        remainder: Money(remainder),
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let mut amount = Money::from_dollars(10);
+        amount += Money::from_cents(50);
+        amount -= Money::from_dollars(1);
+        assert_eq!(amount, Money::from_cents(950));
+        assert_eq!(amount * 2, Money::from_dollars(19));
+        assert_eq!(-amount, Money::from_cents(-950));
+    }
+
+    #[test]
+    fn test_mul_ratio_rounding() {
+        let bet = Money::from_cents(505);
+        assert_eq!(bet.mul_ratio(3, 2, Rounding::Down), Money::from_cents(757));
+        assert_eq!(bet.mul_ratio(3, 2, Rounding::Up), Money::from_cents(758));
+        assert_eq!(bet.mul_ratio(3, 2, Rounding::Nearest), Money::from_cents(758));
+        assert_eq!(Money::from_cents(503).mul_ratio(6, 5, Rounding::Nearest), Money::from_cents(604));
+        assert_eq!(Money::from_dollars(10).mul_ratio(3, 2, Rounding::Down), Money::from_dollars(15));
+    }
+
+    #[test]
+    fn test_mul_ratio_negative_amounts() {
+        let loss = Money::from_cents(-505);
+        assert_eq!(loss.mul_ratio(1, 2, Rounding::Down), Money::from_cents(-253));
+        assert_eq!(loss.mul_ratio(1, 2, Rounding::Up), Money::from_cents(-252));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Money::from_cents(1250).to_string(), "$12.50");
+        assert_eq!(Money::from_cents(-5).to_string(), "-$0.05");
+        assert_eq!(Money::ZERO.to_string(), "$0.00");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("25".parse::<Money>(), Ok(Money::from_dollars(25)));
+        assert_eq!("12.5".parse::<Money>(), Ok(Money::from_cents(1250)));
+        assert_eq!("$7.05".parse::<Money>(), Ok(Money::from_cents(705)));
+        assert_eq!("-3.25".parse::<Money>(), Ok(Money::from_cents(-325)));
+        assert!("1.234".parse::<Money>().is_err());
+        assert!("abc".parse::<Money>().is_err());
+        assert!(".5".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn test_chip_breakdown_standard_chips() {
+        let chips = [1, 5, 25, 100, 500].map(Money::from_dollars);
+        let breakdown = chip_breakdown(Money::from_dollars(2_387), &chips);
+        assert_eq!(breakdown.chips, vec![
+            (Money::from_dollars(500), 4),
+            (Money::from_dollars(100), 3),
+            (Money::from_dollars(25), 3),
+            (Money::from_dollars(5), 2),
+            (Money::from_dollars(1), 2),
+        ]);
+        assert_eq!(breakdown.remainder, Money::ZERO);
+        assert_eq!(breakdown.chip_count(), 14);
+    }
+
+    #[test]
+    fn test_chip_breakdown_non_greedy_set() {
+        let chips = [Money::from_dollars(1), Money::from_cents(250), Money::from_dollars(5)];
+        let breakdown = chip_breakdown(Money::from_dollars(3), &chips);
+        assert_eq!(breakdown.chips, vec![(Money::from_dollars(1), 3)]);
+
+        let breakdown = chip_breakdown(Money::from_cents(750), &chips);
+        assert_eq!(breakdown.chips, vec![(Money::from_dollars(5), 1), (Money::from_cents(250), 1)]);
+    }
+
+    #[test]
+    fn test_chip_breakdown_remainder() {
+        let chips = [1, 5, 25].map(Money::from_dollars);
+        let breakdown = chip_breakdown(Money::from_cents(757), &chips);
+        assert_eq!(breakdown.chips, vec![(Money::from_dollars(5), 1), (Money::from_dollars(1), 2)]);
+        assert_eq!(breakdown.remainder, Money::from_cents(57));
+    }
+
+    #[test]
+    fn test_sum() {
+        let total: Money = vec![Money::from_dollars(1), Money::from_cents(50)].into_iter().sum();
+        assert_eq!(total, Money::from_cents(150));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let amount = Money::from_cents(1250);
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, "\"$12.50\"");
+        assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), amount);
+        assert_eq!(serde_json::from_str::<Money>("25").unwrap(), Money::from_dollars(25));
+        assert_eq!(serde_json::from_str::<Money>("7.05").unwrap(), Money::from_cents(705));
+        assert!(serde_json::from_str::<Money>("\"12.345\"").is_err());
+    }
+}
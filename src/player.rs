@@ -1,21 +1,48 @@
+use std::collections::VecDeque;
 use crate::card::Card;
 use crate::hand::Hand;
+use crate::ledger::{Ledger, TransactionKind};
+use crate::money::Money;
+use uuid::Uuid;
 
 pub struct Player {
     pub hands: Vec<Hand>,
-    pub bank_roll: f64
-    // I'll eventually want to track previous_hands, but not necessary yet
-    // pub previous_hands: Vec<Hand>,
+    /// Hands played in earlier rounds, oldest first
+    pub previous_hands: VecDeque<Hand>,
+    pub bank_roll: Money,
+    /// Every debit and credit made against `bank_roll` this session
+    pub ledger: Ledger,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Player {
     pub fn new() -> Player {
+        let bank_roll = Money::from_dollars(10_000);
         Player {
             hands: vec![Hand::new()],
-            bank_roll: 10_000f64
+            previous_hands: VecDeque::new(),
+            bank_roll,
+            ledger: Ledger::new(bank_roll),
         }
     }
 
+    /// Takes `amount` from the bankroll and records it in the ledger
+    pub fn debit(&mut self, round_id: Uuid, kind: TransactionKind, amount: Money) {
+        self.bank_roll -= amount;
+        self.ledger.record(round_id, kind, -amount, self.bank_roll);
+    }
+
+    /// Adds `amount` to the bankroll and records it in the ledger
+    pub fn credit(&mut self, round_id: Uuid, kind: TransactionKind, amount: Money) {
+        self.bank_roll += amount;
+        self.ledger.record(round_id, kind, amount, self.bank_roll);
+    }
+
     pub fn add_card_to_hand(&mut self, card: Card, hand_index: usize) {
         if let Some(hand) = self.hands.get_mut(hand_index) {
             hand.add_card(card);
@@ -26,11 +53,21 @@ impl Player {
         self.hands = vec![Hand::new()]
     }
 
+    /// Moves the hands just played into `previous_hands`, keeping the most
+    /// recent `limit` of them, and resets for the next round
+    pub fn archive_hands(&mut self, limit: usize) {
+        let played = std::mem::replace(&mut self.hands, vec![Hand::new()]);
+        self.previous_hands.extend(played.into_iter().filter(|hand| !hand.cards.is_empty()));
+        while self.previous_hands.len() > limit {
+            self.previous_hands.pop_front();
+        }
+    }
+
     pub fn print_active_hand(&self) {
         for (i, hand) in self.hands.iter().enumerate() {
             print!("Hand {}: ", i + 1);
             for card in hand.cards.iter() {
-                print!("{} ", card.to_string());
+                print!("{} ", card);
             }
             println!("\n");
         }
@@ -46,7 +83,7 @@ mod tests {
     fn test_new_player() {
         let player = Player::new();
         assert_eq!(player.hands.len(), 1);
-        assert_eq!(player.bank_roll, 10_000f64);
+        assert_eq!(player.bank_roll, Money::from_dollars(10_000));
         assert_eq!(player.hands[0].cards.len(), 0);
     }
 
@@ -82,6 +119,22 @@ mod tests {
         assert_eq!(player.hands[0].cards.len(), 2);
     }
 
+    #[test]
+    fn test_debit_and_credit_are_recorded() {
+        let mut player = Player::new();
+        let round_id = Uuid::new_v4();
+        player.debit(round_id, TransactionKind::Bet, Money::from_dollars(50));
+        player.credit(round_id, TransactionKind::Payout, Money::from_dollars(100));
+
+        assert_eq!(player.bank_roll, Money::from_dollars(10_050));
+        let entries = player.ledger.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].amount, Money::from_dollars(-50));
+        assert_eq!(entries[0].balance, Money::from_dollars(9_950));
+        assert_eq!(entries[1].kind, TransactionKind::Payout);
+        assert_eq!(player.ledger.round_net(round_id), Money::from_dollars(50));
+    }
+
     #[test]
     fn test_reset_hands() {
         let mut player = Player::new();
@@ -93,4 +146,20 @@ mod tests {
         player.reset_hands();
         assert_eq!(player.hands[0].cards.len(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_archive_hands() {
+        let mut player = Player::new();
+        for rank in [Rank::Two, Rank::Three, Rank::Four] {
+            player.add_card_to_hand(Card::new(rank, Suit::Spades), 0);
+            player.archive_hands(2);
+        }
+        // An unplayed hand is not kept
+        player.archive_hands(2);
+
+        assert_eq!(player.hands[0].cards.len(), 0);
+        assert_eq!(player.previous_hands.len(), 2);
+        assert_eq!(player.previous_hands[0].cards[0].rank, Rank::Three);
+        assert_eq!(player.previous_hands[1].cards[0].rank, Rank::Four);
+    }
+}
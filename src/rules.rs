@@ -0,0 +1,403 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use strum_macros::EnumIter;
+use crate::house_edge::house_edge;
+use crate::money::{Money, Rounding};
+
+/// A payout ratio such as 3:2, paying `numerator` for every `denominator` bet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payout {
+    pub numerator: i64,
+    pub denominator: i64,
+}
+
+impl Payout {
+    pub const THREE_TO_TWO: Payout = Payout { numerator: 3, denominator: 2 };
+    pub const SIX_TO_FIVE: Payout = Payout { numerator: 6, denominator: 5 };
+    pub const EVEN_MONEY: Payout = Payout { numerator: 1, denominator: 1 };
+
+    /// Winnings on `bet`, not including the returned stake
+    pub fn winnings(&self, bet: Money, rounding: Rounding) -> Money {
+        bet.mul_ratio(self.numerator, self.denominator, rounding)
+    }
+
+    /// The payout as a multiple of the bet, e.g. 1.5 for 3:2
+    pub fn ratio(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+impl fmt::Display for Payout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.numerator, self.denominator)
+    }
+}
+
+impl FromStr for Payout {
+    type Err = String;
+
+    /// Parses ratios like "3:2" or "6:5"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid payout '{}', expected a ratio such as 3:2", s);
+        let (numerator, denominator) = s.trim().split_once(':').ok_or_else(invalid)?;
+        Ok(Payout {
+            numerator: numerator.trim().parse().map_err(|_| invalid())?,
+            denominator: denominator.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl Serialize for Payout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Payout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Which two-card hands may be doubled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoubleRule {
+    #[default]
+    AnyTwoCards,
+    /// Hard 9, 10 or 11 only
+    NineToEleven,
+    /// Hard 10 or 11 only
+    TenOrEleven,
+}
+
+impl DoubleRule {
+    /// Whether a two-card hand with this total may be doubled
+    pub fn allows(&self, total: u32, soft: bool) -> bool {
+        match self {
+            DoubleRule::AnyTwoCards => true,
+            DoubleRule::NineToEleven => !soft && (9..=11).contains(&total),
+            DoubleRule::TenOrEleven => !soft && (10..=11).contains(&total),
+        }
+    }
+}
+
+impl fmt::Display for DoubleRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DoubleRule::AnyTwoCards => "any two cards",
+            DoubleRule::NineToEleven => "9, 10 or 11",
+            DoubleRule::TenOrEleven => "10 or 11",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The game being dealt: classic blackjack or one of its variants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameVariant {
+    #[default]
+    Classic,
+    /// 48-card decks with the tens removed. A player 21 always wins, five or
+    /// more card 21s and 6-7-8 or 7-7-7 pay bonuses, any number of cards may be
+    /// doubled, and a doubled hand can be rescued by surrendering the original bet
+    Spanish21,
+    /// Two hands whose second cards may be swapped before play. Blackjack pays
+    /// 1:1 and a dealer 22 pushes every hand but a natural
+    BlackjackSwitch,
+    /// Two-card hard 9, 10 and 11 double for free, pairs other than tens split
+    /// for free, and a dealer 22 pushes every hand but a natural
+    FreeBet,
+    /// Both dealer cards are dealt face up. The dealer wins ties, except that a
+    /// player blackjack beats a dealer blackjack, and blackjack pays 1:1
+    DoubleExposure,
+    /// British Pontoon: players twist and stick, and may not stick below 15.
+    /// Both dealer cards stay face down, the dealer wins ties and a five-card
+    /// trick pays 2:1
+    Pontoon,
+}
+
+impl GameVariant {
+    /// Returns true if the player sees the dealer's hole card while playing
+    pub fn exposes_hole_card(&self) -> bool {
+        matches!(self, GameVariant::DoubleExposure)
+    }
+
+    /// Returns true if the player cannot see either dealer card while playing
+    pub fn hides_dealer_cards(&self) -> bool {
+        matches!(self, GameVariant::Pontoon)
+    }
+
+    /// Returns true if a tie loses rather than pushes
+    pub fn dealer_wins_ties(&self) -> bool {
+        matches!(self, GameVariant::DoubleExposure | GameVariant::Pontoon)
+    }
+
+    /// The lowest total a player may stand on, if the variant sets one
+    pub fn minimum_stand(&self) -> Option<u32> {
+        match self {
+            GameVariant::Pontoon => Some(15),
+            _ => None,
+        }
+    }
+
+    /// Cards in each deck the variant is dealt from: 48 for Spanish 21, whose
+    /// decks have no tens, otherwise 52
+    pub fn cards_per_deck(&self) -> usize {
+        match self {
+            GameVariant::Spanish21 => 48,
+            _ => 52,
+        }
+    }
+
+    /// Returns true if a doubled hand stays in play after its card rather than
+    /// standing: Spanish 21 offers a rescue, and a Pontoon hand that buys may twist
+    pub fn plays_on_after_double(&self) -> bool {
+        matches!(self, GameVariant::Spanish21 | GameVariant::Pontoon)
+    }
+
+    /// Returns true if a player blackjack beats a dealer blackjack instead of pushing
+    pub fn natural_beats_dealer_natural(&self) -> bool {
+        matches!(self, GameVariant::Spanish21 | GameVariant::DoubleExposure)
+    }
+
+    /// Returns true if a dealer 22 pushes rather than loses against player hands
+    pub fn dealer_22_pushes(&self) -> bool {
+        matches!(self, GameVariant::BlackjackSwitch | GameVariant::FreeBet)
+    }
+}
+
+impl fmt::Display for GameVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GameVariant::Classic => "Classic",
+            GameVariant::Spanish21 => "Spanish 21",
+            GameVariant::BlackjackSwitch => "Blackjack Switch",
+            GameVariant::FreeBet => "Free Bet",
+            GameVariant::DoubleExposure => "Double Exposure",
+            GameVariant::Pontoon => "Pontoon",
+        })
+    }
+}
+
+/// Playing rules that change the game's odds
+///
+/// Rules missing from a config file take their value from `Rules::new`.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Classic blackjack or a variant with its own deck, payouts and rule hooks
+    pub variant: GameVariant,
+    /// The dealer hits soft 17 (H17) rather than standing on it (S17)
+    pub dealer_hits_soft_17: bool,
+    /// Split hands may be doubled
+    pub double_after_split: bool,
+    /// Which first two cards may be doubled
+    pub double_on: DoubleRule,
+    /// Most hands a player can end up with by splitting and resplitting
+    pub max_split_hands: u8,
+    /// Split aces that receive another ace may be split again
+    pub resplit_aces: bool,
+    /// Split aces may be played on; otherwise each takes a single card
+    pub hit_split_aces: bool,
+    /// The first two cards may be surrendered for half the bet once the dealer
+    /// has checked for blackjack
+    pub late_surrender: bool,
+    /// European style: the dealer takes a second card only after the players
+    /// finish, and a dealer blackjack takes every bet including doubles and splits
+    pub no_hole_card: bool,
+    /// Payout for a natural blackjack
+    pub blackjack_payout: Payout,
+    /// A hand that reaches this many cards without busting wins at once,
+    /// whatever the dealer makes; `None` if the table has no charlie rule
+    pub charlie: Option<u8>,
+}
+
+impl Rules {
+    /// Dealer stands on soft 17, double any two cards including after a split,
+    /// split to four hands, split aces take one card, blackjack pays 3:2
+    pub fn new() -> Self {
+        Self {
+            variant: GameVariant::Classic,
+            dealer_hits_soft_17: false,
+            double_after_split: true,
+            double_on: DoubleRule::AnyTwoCards,
+            max_split_hands: 4,
+            resplit_aces: false,
+            hit_split_aces: false,
+            late_surrender: false,
+            no_hole_card: false,
+            blackjack_payout: Payout::THREE_TO_TWO,
+            charlie: None,
+        }
+    }
+
+    /// What a natural actually pays: `blackjack_payout`, or 1:1 in Blackjack
+    /// Switch and Double Exposure
+    pub fn natural_payout(&self) -> Payout {
+        match self.variant {
+            GameVariant::BlackjackSwitch | GameVariant::DoubleExposure => Payout::EVEN_MONEY,
+            _ => self.blackjack_payout,
+        }
+    }
+
+    /// How many cards make a charlie: `charlie`, or the five-card trick in Pontoon
+    pub fn charlie_cards(&self) -> Option<u8> {
+        match self.variant {
+            GameVariant::Pontoon => Some(5),
+            _ => self.charlie,
+        }
+    }
+
+    /// What a charlie pays: 2:1 for a Pontoon five-card trick, otherwise even money
+    pub fn charlie_payout(&self) -> Payout {
+        match self.variant {
+            GameVariant::Pontoon => Payout { numerator: 2, denominator: 1 },
+            _ => Payout::EVEN_MONEY,
+        }
+    }
+
+    /// Approximate basic strategy player edge off the top of the shoe, as a
+    /// fraction of the initial bet (negative when the house has the edge), or
+    /// `None` if `house_edge` has no estimate for the game
+    pub fn player_edge(&self, deck_count: usize) -> Option<f64> {
+        house_edge(self, deck_count).map(|edge| -edge.total())
+    }
+
+    /// Each rule's name and setting, in a fixed order, for display and comparison
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+        vec![
+            ("Game", self.variant.to_string()),
+            ("Dealer soft 17", if self.dealer_hits_soft_17 { "hits" } else { "stands" }.to_string()),
+            ("Double on", self.double_on.to_string()),
+            ("Double after split", yes_no(self.double_after_split)),
+            ("Split up to", format!("{} hands", self.max_split_hands)),
+            ("Resplit aces", yes_no(self.resplit_aces)),
+            ("Hit split aces", yes_no(self.hit_split_aces)),
+            ("Late surrender", yes_no(self.late_surrender)),
+            ("Dealer hole card", if self.no_hole_card { "none" } else { "peeks" }.to_string()),
+            ("Blackjack pays", self.natural_payout().to_string()),
+            ("Charlie", match self.charlie_cards() {
+                None => "none".to_string(),
+                Some(cards) if self.charlie_payout() == Payout::EVEN_MONEY => format!("{} cards", cards),
+                Some(cards) => format!("{} cards pays {}", cards, self.charlie_payout()),
+            }),
+        ]
+    }
+
+    /// Rules that differ between `self` and `other`, as "name: ours vs theirs"
+    pub fn differences(&self, other: &Rules) -> Vec<String> {
+        self.summary().into_iter()
+            .zip(other.summary())
+            .filter(|((_, ours), (_, theirs))| ours != theirs)
+            .map(|((name, ours), (_, theirs))| format!("{}: {} vs {}", name, ours, theirs))
+            .collect()
+    }
+
+    /// Checks that the rules make sense together
+    ///
+    /// # Returns
+    /// `Ok(())` if the rules are consistent, `Err` with description if not
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_with_key().map_err(|(_, message)| message)
+    }
+
+    /// Like `validate`, but also names the rule at fault, e.g. `max_split_hands`
+    pub fn validate_with_key(&self) -> Result<(), (&'static str, String)> {
+        let payout = self.blackjack_payout;
+        if payout.numerator <= 0 || payout.denominator <= 0 {
+            return Err(("blackjack_payout", "Blackjack payout must be a positive ratio".to_string()));
+        }
+        if self.max_split_hands < 2 {
+            return Err(("max_split_hands", "Players must be allowed at least 2 hands when splitting".to_string()));
+        }
+        if self.resplit_aces && self.max_split_hands <= 2 {
+            return Err(("resplit_aces", "Resplitting aces needs a split limit above 2 hands".to_string()));
+        }
+        if self.charlie.is_some_and(|cards| cards < 5) {
+            return Err(("charlie", "A charlie needs at least 5 cards".to_string()));
+        }
+        if self.variant == GameVariant::Pontoon && self.charlie.is_some() {
+            return Err(("charlie", "Pontoon already pays a five-card trick, so it takes no charlie rule".to_string()));
+        }
+        if self.variant.hides_dealer_cards() && self.no_hole_card {
+            return Err(("no_hole_card", format!("{} deals the dealer two face-down cards, so it needs a hole card", self.variant)));
+        }
+        if self.variant.exposes_hole_card() && self.no_hole_card {
+            return Err(("no_hole_card", format!("{} deals both dealer cards face up, so it needs a hole card", self.variant)));
+        }
+        if self.late_surrender && self.no_hole_card {
+            return Err((
+                "late_surrender",
+                "Late surrender needs the dealer to check for blackjack, which a no hole card game does not do".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payout() {
+        let bet = Money::from_cents(505);
+        assert_eq!(Payout::THREE_TO_TWO.winnings(bet, Rounding::Down), Money::from_cents(757));
+        assert_eq!(Payout::SIX_TO_FIVE.winnings(Money::from_dollars(10), Rounding::Down), Money::from_dollars(12));
+        assert_eq!(Payout::SIX_TO_FIVE.to_string(), "6:5");
+    }
+
+    #[test]
+    fn test_parse_payout() {
+        assert_eq!("6:5".parse::<Payout>().unwrap(), Payout::SIX_TO_FIVE);
+        assert_eq!(" 3 : 2 ".parse::<Payout>().unwrap(), Payout::THREE_TO_TWO);
+        assert!("3-2".parse::<Payout>().is_err());
+    }
+
+    #[test]
+    fn test_differences() {
+        let strip = Rules { late_surrender: true, ..Rules::new() };
+        let downtown = Rules { dealer_hits_soft_17: true, ..Rules::new() };
+        assert_eq!(strip.differences(&downtown), vec![
+            "Dealer soft 17: stands vs hits".to_string(),
+            "Late surrender: yes vs no".to_string(),
+        ]);
+        assert!(strip.differences(&strip).is_empty());
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        assert!(Rules::new().validate().is_ok());
+        let rules = Rules { late_surrender: true, no_hole_card: true, ..Rules::new() };
+        assert!(rules.validate().is_err());
+        let rules = Rules { resplit_aces: true, max_split_hands: 2, ..Rules::new() };
+        assert_eq!(rules.validate().unwrap_err(), "Resplitting aces needs a split limit above 2 hands");
+        let rules = Rules { charlie: Some(4), ..Rules::new() };
+        assert_eq!(rules.validate_with_key().unwrap_err().0, "charlie");
+        assert!(Rules { charlie: Some(6), ..Rules::new() }.validate().is_ok());
+        let rules = Rules { variant: GameVariant::Pontoon, charlie: Some(5), ..Rules::new() };
+        assert_eq!(rules.validate_with_key().unwrap_err().0, "charlie");
+    }
+
+    #[test]
+    fn test_double_rule() {
+        assert!(DoubleRule::AnyTwoCards.allows(18, true));
+        assert!(DoubleRule::NineToEleven.allows(9, false));
+        assert!(!DoubleRule::NineToEleven.allows(19, true));
+        assert!(!DoubleRule::TenOrEleven.allows(9, false));
+        assert!(DoubleRule::TenOrEleven.allows(11, false));
+    }
+}
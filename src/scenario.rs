@@ -0,0 +1,220 @@
+use crate::card::Card;
+use crate::game::Game;
+use crate::game_settings::GameSettings;
+use crate::rules::{GameVariant, Rules};
+use crate::shoe::Shoe;
+
+/// Describes a stacked deal, e.g. "you have 8,8 vs dealer 10", for lessons and tests
+///
+/// The shoe is built so that the player and dealer receive exactly the requested
+/// cards from `Game::deal_initial_cards`, followed by any `then` cards. Everything
+/// else in the shoe is shuffled underneath.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scenario {
+    /// The player's two starting cards
+    pub player_cards: Vec<Card>,
+    /// Two starting cards for the second hand of a Blackjack Switch game
+    pub second_hand_cards: Vec<Card>,
+    /// The dealer's upcard, optionally followed by the hole card
+    pub dealer_cards: Vec<Card>,
+    /// Cards drawn after the initial deal, in order
+    pub next_cards: Vec<Card>,
+}
+
+impl Scenario {
+    /// Creates an empty scenario
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the player's two starting cards
+    pub fn player(mut self, cards: Vec<Card>) -> Self {
+        self.player_cards = cards;
+        self
+    }
+
+    /// Sets the two starting cards of the second Blackjack Switch hand
+    pub fn second_hand(mut self, cards: Vec<Card>) -> Self {
+        self.second_hand_cards = cards;
+        self
+    }
+
+    /// Sets the dealer's upcard and, optionally, the hole card
+    ///
+    /// When only the upcard is given the hole card is drawn from the shuffled
+    /// remainder of the shoe.
+    pub fn dealer(mut self, cards: Vec<Card>) -> Self {
+        self.dealer_cards = cards;
+        self
+    }
+
+    /// Appends cards that will be drawn, in order, once the initial deal is done
+    pub fn then(mut self, cards: Vec<Card>) -> Self {
+        self.next_cards.extend(cards);
+        self
+    }
+
+    /// Builds a shuffled shoe of `num_decks` decks with the scenario's cards on top
+    ///
+    /// # Returns
+    /// `Err` with a description if the scenario is incomplete or asks for more
+    /// copies of a card than the shoe holds
+    pub fn build_shoe(&self, num_decks: usize) -> Result<Shoe, String> {
+        self.stack_shoe(num_decks, &Rules::new())
+    }
+
+    /// Builds the shoe for a game with `rules`, which decide the kind of deck and
+    /// whether the dealer takes a hole card
+    ///
+    /// Without a hole card the dealer's second card, if given, comes straight
+    /// after the `then` cards, so those should be exactly the player's draws.
+    fn stack_shoe(&self, num_decks: usize, rules: &Rules) -> Result<Shoe, String> {
+        let hole_card = !rules.no_hole_card;
+        if self.player_cards.len() != 2 {
+            return Err("Scenario needs exactly two player cards".to_string());
+        }
+        let switch = rules.variant == GameVariant::BlackjackSwitch;
+        if self.second_hand_cards.len() != if switch { 2 } else { 0 } {
+            return Err("Scenario needs two second hand cards for Blackjack Switch, and none otherwise".to_string());
+        }
+        if !(1..=2).contains(&self.dealer_cards.len()) {
+            return Err("Scenario needs a dealer upcard and at most one hole card".to_string());
+        }
+
+        let mut shoe = Shoe::for_variant(num_decks, rules.variant);
+        shoe.shuffle();
+
+        let player = take_cards(&mut shoe, &self.player_cards, num_decks)?;
+        let second_hand = take_cards(&mut shoe, &self.second_hand_cards, num_decks)?;
+        let mut dealer = take_cards(&mut shoe, &self.dealer_cards, num_decks)?;
+        let next = take_cards(&mut shoe, &self.next_cards, num_decks)?;
+
+        if hole_card && dealer.len() == 1 {
+            // Random hole card from whatever is left in the shoe
+            match shoe.cards.pop() {
+                Some(card) => dealer.push(card),
+                None => return Err("No cards left for the dealer's hole card".to_string()),
+            }
+        }
+
+        // Deal order matches Game::deal_initial_cards: each player hand, then the
+        // dealer, twice over, except that a no hole card dealer draws last
+        let mut order = Vec::new();
+        for round in 0..2 {
+            order.push(player[round].clone());
+            order.extend(second_hand.get(round).cloned());
+            if round == 0 || hole_card {
+                order.push(dealer[round].clone());
+            }
+        }
+        order.extend(next);
+        if !hole_card {
+            order.extend(dealer.into_iter().skip(1));
+        }
+        shoe.stack(order);
+        Ok(shoe)
+    }
+
+    /// Builds a game using `settings` whose shoe is stacked with this scenario
+    ///
+    /// # Returns
+    /// `Err` if the settings are invalid or the scenario's cards do not exist in
+    /// the configured number of decks
+    pub fn build_game(&self, settings: GameSettings) -> Result<Game, String> {
+        settings.validate()?;
+        let shoe = self.stack_shoe(settings.deck_count as usize, &settings.rules)?;
+        let mut game = Game::new(settings);
+        game.shoe = shoe;
+        Ok(game)
+    }
+}
+
+fn take_cards(shoe: &mut Shoe, cards: &[Card], num_decks: usize) -> Result<Vec<Card>, String> {
+    cards.iter()
+        .map(|card| {
+            shoe.remove_card(card).ok_or_else(|| {
+                format!("Scenario uses more {} than a {}-deck shoe holds", card, num_decks)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{Rank, Suit};
+    use crate::game::{GameAction, GameState};
+    use crate::money::Money;
+
+    fn settings(deck_count: u8) -> GameSettings {
+        GameSettings::new("Student".to_string(), deck_count)
+    }
+
+    #[test]
+    fn test_scenario_deals_requested_cards() {
+        let scenario = Scenario::new()
+            .player(vec![Card::new(Rank::Eight, Suit::Spades), Card::new(Rank::Eight, Suit::Hearts)])
+            .dealer(vec![Card::new(Rank::Ten, Suit::Clubs), Card::new(Rank::Seven, Suit::Clubs)]);
+        let mut game = scenario.build_game(settings(6)).unwrap();
+
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+
+        assert_eq!(game.player.hands[0].cards, scenario.player_cards);
+        assert_eq!(game.dealer.hands[0].cards, scenario.dealer_cards);
+        assert!(matches!(game.get_state(), GameState::PlayerTurn { .. }));
+        assert_eq!(game.shoe.cards.len() + game.shoe.discarded.len(), 6 * 52);
+    }
+
+    #[test]
+    fn test_scenario_next_cards_follow_deal() {
+        let scenario = Scenario::new()
+            .player(vec![Card::new(Rank::Five, Suit::Spades), Card::new(Rank::Six, Suit::Hearts)])
+            .dealer(vec![Card::new(Rank::Six, Suit::Clubs), Card::new(Rank::Ten, Suit::Clubs)])
+            .then(vec![Card::new(Rank::King, Suit::Diamonds)]);
+        let mut game = scenario.build_game(settings(1)).unwrap();
+
+        game.accept_user_bet(Money::from_dollars(10)).unwrap();
+        game.deal_initial_cards();
+        game.process_player_action(GameAction::Double, 0).unwrap();
+
+        assert_eq!(game.player.hands[0].cards[2], Card::new(Rank::King, Suit::Diamonds));
+        assert_eq!(game.player.hands[0].best_value(), 21);
+    }
+
+    #[test]
+    fn test_scenario_random_hole_card() {
+        let upcard = Card::new(Rank::Ten, Suit::Hearts);
+        let shoe = Scenario::new()
+            .player(vec![Card::new(Rank::Eight, Suit::Spades), Card::new(Rank::Eight, Suit::Hearts)])
+            .dealer(vec![upcard.clone()])
+            .build_shoe(1)
+            .unwrap();
+
+        assert_eq!(shoe.cards.len(), 52);
+        assert_eq!(shoe.cards[shoe.cards.len() - 2], upcard);
+    }
+
+    #[test]
+    fn test_scenario_rejects_cards_missing_from_shoe() {
+        let ace = Card::new(Rank::Ace, Suit::Spades);
+        let scenario = Scenario::new()
+            .player(vec![ace.clone(), ace.clone()])
+            .dealer(vec![Card::new(Rank::Ten, Suit::Clubs)]);
+
+        assert!(scenario.build_game(settings(1)).is_err());
+        assert!(scenario.build_game(settings(2)).is_ok());
+    }
+
+    #[test]
+    fn test_scenario_requires_two_player_cards() {
+        let scenario = Scenario::new()
+            .player(vec![Card::new(Rank::Eight, Suit::Spades)])
+            .dealer(vec![Card::new(Rank::Ten, Suit::Clubs)]);
+
+        assert_eq!(
+            scenario.build_shoe(1).err().unwrap(),
+            "Scenario needs exactly two player cards"
+        );
+    }
+}
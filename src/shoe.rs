@@ -2,25 +2,58 @@ use std::thread::sleep;
 use std::time::Duration;
 use strum::IntoEnumIterator;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 use crate::card::{Card, Rank, Suit};
+use crate::counting::CountingSystem;
+use crate::rules::GameVariant;
+
+/// When the dealt cards go back into the shoe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShuffleMode {
+    /// The whole shoe is reshuffled once the cut card comes out
+    #[default]
+    CutCard,
+    /// A continuous shuffling machine takes the discards back after every
+    /// round, apart from the `buffer` most recently dealt cards it has not yet
+    /// shuffled in, so the count never gets a chance to build up
+    Continuous { buffer: usize },
+}
 
 pub struct Shoe {
     pub cards: Vec<Card>,
     pub discarded: Vec<Card>,
-    number_of_decks: usize
-    // Count will be implemented at a later date
-    // pub count: i32,
+    number_of_decks: usize,
+    /// 52, or 48 for Spanish 21 decks, which have their four tens removed
+    cards_per_deck: usize,
 }
 
 impl Shoe {
     pub fn new(num_decks: usize) -> Self {
+        Self::for_variant(num_decks, GameVariant::Classic)
+    }
+
+    /// Creates a shoe of 48-card Spanish decks, which have no tens but keep
+    /// their jacks, queens and kings
+    pub fn spanish(num_decks: usize) -> Self {
+        Self::for_variant(num_decks, GameVariant::Spanish21)
+    }
+
+    /// Creates the shoe a game variant is dealt from
+    pub fn for_variant(num_decks: usize, variant: GameVariant) -> Self {
+        Self::build(num_decks, variant.cards_per_deck())
+    }
+
+    fn build(num_decks: usize, cards_per_deck: usize) -> Self {
         // Initialize a vector w/ size defined upfront
-        let capacity = 52 * num_decks;
+        let capacity = cards_per_deck * num_decks;
         let mut cards: Vec<Card> = Vec::with_capacity(capacity);
 
         for _ in 0..num_decks {
             cards.extend(
                 Rank::iter()
+                    .filter(|rank| cards_per_deck == 52 || *rank != Rank::Ten)
                     .flat_map(|rank| {
                         Suit::iter().map(move |suit| Card::new(rank.clone(), suit))
                     })
@@ -31,7 +64,8 @@ impl Shoe {
         Shoe {
             cards,
             discarded: Vec::with_capacity(capacity),
-            number_of_decks: num_decks
+            number_of_decks: num_decks,
+            cards_per_deck,
         }
     }
 
@@ -41,12 +75,65 @@ impl Shoe {
     }
 
     pub fn print_deck(&self) {
-        for (_val, i) in self.cards.iter().enumerate() {
-            print!("{}", i.rank.to_string());
-            println!("{}", i.suit.to_string());
+        for card in self.cards.iter() {
+            println!("{}", card);
         }
     }
 
+    /// Removes one copy of `card` from the undealt cards, if the shoe still holds one
+    pub fn remove_card(&mut self, card: &Card) -> Option<Card> {
+        let position = self.cards.iter().position(|c| c == card)?;
+        Some(self.cards.remove(position))
+    }
+
+    /// Places `cards` on top of the shoe so that `draw_card` returns them in the given order
+    pub fn stack(&mut self, cards: Vec<Card>) {
+        self.cards.extend(cards.into_iter().rev());
+    }
+
+    /// Returns every discarded card to the shoe and shuffles
+    pub fn reshuffle(&mut self) {
+        self.cards.append(&mut self.discarded);
+        self.shuffle();
+    }
+
+    /// Returns the discards to the shoe like a continuous shuffling machine,
+    /// holding out the `buffer` most recently dealt cards, and shuffles them in
+    /// with the cards still to be dealt
+    pub fn shuffle_in_discards(&mut self, buffer: usize) {
+        let returned = self.discarded.len().saturating_sub(buffer);
+        self.cards.extend(self.discarded.drain(..returned));
+        self.shuffle();
+    }
+
+    /// Fraction of the shoe dealt since the last shuffle
+    pub fn penetration(&self) -> f64 {
+        let total = self.cards.len() + self.discarded.len();
+        if total == 0 {
+            return 0f64;
+        }
+        self.discarded.len() as f64 / total as f64
+    }
+
+    pub fn number_of_decks(&self) -> usize {
+        self.number_of_decks
+    }
+
+    /// Number of cards in each of the shoe's decks: 52, or 48 for Spanish decks
+    pub fn cards_per_deck(&self) -> usize {
+        self.cards_per_deck
+    }
+
+    /// Decks left to deal, for true count conversion
+    pub fn decks_remaining(&self) -> f64 {
+        self.cards.len() as f64 / self.cards_per_deck() as f64
+    }
+
+    /// Running count of every card drawn since the last shuffle, face down or not
+    pub fn running_count(&self, system: CountingSystem) -> i32 {
+        system.initial_running_count(self.number_of_decks) + system.count_cards(&self.discarded)
+    }
+
     pub fn draw_card(&mut self) -> Option<Card> {
         let card = self.cards.pop()?;
         self.discarded.push(card.clone());
@@ -60,7 +147,7 @@ impl Shoe {
 
         if self.cards.len() < min_cards_needed {
             // Create new shoe with calculated number of decks
-            let new_shoe = Shoe::new(self.number_of_decks);
+            let new_shoe = Shoe::build(self.number_of_decks, self.cards_per_deck);
             self.cards = new_shoe.cards;
             self.discarded.clear();
 
@@ -95,6 +182,24 @@ mod tests {
         assert_eq!(shoe.cards.len(), num_decks * 52);
     }
 
+    #[test]
+    fn test_spanish_shoe_has_no_tens() {
+        let shoe = Shoe::for_variant(2, GameVariant::Spanish21);
+        assert_eq!(shoe.cards.len(), 2 * 48);
+        assert!(shoe.cards.iter().all(|card| card.rank != Rank::Ten));
+        assert_eq!(shoe.cards.iter().filter(|card| card.rank == Rank::King).count(), 8);
+        assert_eq!(shoe.decks_remaining(), 2f64);
+    }
+
+    #[test]
+    fn test_variant_deck_size_matches_its_shoe() {
+        for variant in GameVariant::iter() {
+            let shoe = Shoe::for_variant(1, variant);
+            assert_eq!(shoe.cards.len(), variant.cards_per_deck(), "{}", variant);
+            assert_eq!(shoe.cards_per_deck(), variant.cards_per_deck(), "{}", variant);
+        }
+    }
+
     #[test]
     fn test_draw_card() {
         let mut shoe = Shoe::new(1);
@@ -117,6 +222,75 @@ mod tests {
         assert_eq!(shoe.discarded.len(), 52); // Full deck in discard
     }
 
+    #[test]
+    fn test_stack_draws_in_order() {
+        let mut shoe = Shoe::new(1);
+        let first = shoe.remove_card(&Card::new(Rank::Eight, Suit::Spades)).unwrap();
+        let second = shoe.remove_card(&Card::new(Rank::Eight, Suit::Hearts)).unwrap();
+        shoe.stack(vec![first.clone(), second.clone()]);
+
+        assert_eq!(shoe.cards.len(), 52);
+        assert_eq!(shoe.draw_card(), Some(first));
+        assert_eq!(shoe.draw_card(), Some(second));
+    }
+
+    #[test]
+    fn test_remove_card_missing_from_shoe() {
+        let mut shoe = Shoe::new(1);
+        let ace = Card::new(Rank::Ace, Suit::Spades);
+
+        assert!(shoe.remove_card(&ace).is_some());
+        assert!(shoe.remove_card(&ace).is_none());
+        assert_eq!(shoe.cards.len(), 51);
+    }
+
+    #[test]
+    fn test_running_count_tracks_discards() {
+        let mut shoe = Shoe::new(2);
+        let cards = vec![
+            shoe.remove_card(&Card::new(Rank::Five, Suit::Spades)).unwrap(),
+            shoe.remove_card(&Card::new(Rank::Six, Suit::Hearts)).unwrap(),
+            shoe.remove_card(&Card::new(Rank::King, Suit::Clubs)).unwrap(),
+        ];
+        shoe.stack(cards);
+        assert_eq!(shoe.running_count(CountingSystem::HiLo), 0);
+        for _ in 0..3 {
+            shoe.draw_card();
+        }
+
+        assert_eq!(shoe.running_count(CountingSystem::HiLo), 1);
+        assert_eq!(shoe.running_count(CountingSystem::KO), -3);
+        assert_eq!(shoe.decks_remaining(), 101f64 / 52f64);
+    }
+
+    #[test]
+    fn test_reshuffle_returns_discards() {
+        let mut shoe = Shoe::new(1);
+        for _ in 0..39 {
+            shoe.draw_card();
+        }
+        assert_eq!(shoe.penetration(), 0.75);
+
+        shoe.reshuffle();
+        assert_eq!(shoe.cards.len(), 52);
+        assert!(shoe.discarded.is_empty());
+        assert_eq!(shoe.penetration(), 0f64);
+    }
+
+    #[test]
+    fn test_shuffle_in_discards_holds_out_buffer() {
+        let mut shoe = Shoe::new(1);
+        let dealt: Vec<Card> = (0..10).filter_map(|_| shoe.draw_card()).collect();
+
+        shoe.shuffle_in_discards(4);
+        assert_eq!(shoe.cards.len(), 48);
+        assert_eq!(shoe.discarded, dealt[6..]);
+
+        shoe.shuffle_in_discards(0);
+        assert_eq!(shoe.cards.len(), 52);
+        assert!(shoe.discarded.is_empty());
+    }
+
     #[test]
     fn test_multiple_deck_size() {
         for num_decks in 1..=8 {
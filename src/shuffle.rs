@@ -0,0 +1,252 @@
+use std::ops::Range;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::shoe::Shoe;
+
+/// A dealer's hand shuffle, for shuffle tracking practice
+///
+/// The discard tray is picked up with the unplayed stub plugged into it and
+/// split into two piles. A grab from each pile is riffled together, strip cut
+/// and riffled again, and the finished grabs are stacked into the new shoe,
+/// which is box cut before it goes back in play. Unlike `Shoe::shuffle`, the
+/// result is far from uniform, so a slug of cards can be followed through it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CasinoShuffle {
+    /// Push the unplayed stub into the discards at a random point rather than
+    /// setting it on top of them
+    pub plug: bool,
+    /// Cards taken from each pile for every riffle
+    pub grab_size: usize,
+    /// Riffles given to each grab, with a strip cut before every riffle after the first
+    pub riffles: u8,
+    /// Packets pulled off the top in a strip cut
+    pub strip_packets: u8,
+    /// Packets the finished shoe is cut into and restacked bottom to top; below 2 skips the box
+    pub box_packets: u8,
+    /// 0 for cuts at exactly the middle and riffles that drop one card from
+    /// each side in turn, up to 1 for ragged cuts and cards dropping in clumps
+    pub imperfection: f64,
+}
+
+impl CasinoShuffle {
+    /// Plugged stub, half-deck grabs riffled twice around a four-packet strip,
+    /// a four-packet box and a moderately sloppy dealer
+    pub fn new() -> Self {
+        Self {
+            plug: true,
+            grab_size: 26,
+            riffles: 2,
+            strip_packets: 4,
+            box_packets: 4,
+            imperfection: 0.5,
+        }
+    }
+
+    /// Returns the discards and the stub to the shoe in a new order, as a dealer
+    /// shuffling by hand would leave them
+    ///
+    /// # Returns
+    /// Where each discard ended up, for following slugs into the new shoe
+    pub fn shuffle(&self, shoe: &mut Shoe) -> ShuffleTrace {
+        self.shuffle_with(shoe, &mut rand::rng())
+    }
+
+    fn shuffle_with(&self, shoe: &mut Shoe, rng: &mut impl Rng) -> ShuffleTrace {
+        // Work with each card's pick-up number, listed from the top of the stack down:
+        // discards are numbered in the order they were dealt, the stub after them from
+        // the bottom up, as `cards` holds it
+        let discard_count = shoe.discarded.len();
+        let mut stack: Vec<usize> = (0..discard_count).rev().collect();
+        let stub = (discard_count..discard_count + shoe.cards.len()).rev();
+        let plug_at = if self.plug { rng.random_range(0..=stack.len()) } else { 0 };
+        stack.splice(plug_at..plug_at, stub);
+
+        let split = self.cut_point(stack.len(), rng);
+        let mut right = stack.split_off(split);
+        let mut left = stack;
+        let mut shuffled = Vec::with_capacity(left.len() + right.len());
+        while !left.is_empty() || !right.is_empty() {
+            let from_left: Vec<usize> = left.drain(..self.grab_size.min(left.len())).collect();
+            let from_right: Vec<usize> = right.drain(..self.grab_size.min(right.len())).collect();
+            let mut grab = self.riffle(from_left, from_right, rng);
+            for _ in 1..self.riffles {
+                grab = self.cut_into_packets(grab, self.strip_packets, rng);
+                let half = grab.split_off(self.cut_point(grab.len(), rng));
+                grab = self.riffle(grab, half, rng);
+            }
+            // Each finished grab is set down on top of the last
+            grab.append(&mut shuffled);
+            shuffled = grab;
+        }
+        let shuffled = self.cut_into_packets(shuffled, self.box_packets, rng);
+
+        let mut picked_up: Vec<Option<_>> = shoe.discarded.drain(..).map(Some).collect();
+        picked_up.extend(shoe.cards.drain(..).map(Some));
+        let mut discard_positions = vec![0; discard_count];
+        for (position, &number) in shuffled.iter().enumerate() {
+            if number < discard_count {
+                discard_positions[number] = position;
+            }
+        }
+        // The shoe deals from the end of `cards`, so the top of the stack goes last
+        shoe.cards = shuffled.iter().rev().filter_map(|&number| picked_up[number].take()).collect();
+        ShuffleTrace { discard_positions }
+    }
+
+    /// Where a dealer aiming for the middle of `len` cards actually cuts
+    fn cut_point(&self, len: usize, rng: &mut impl Rng) -> usize {
+        let spread = (self.imperfection * len as f64 / 8.0).round() as usize;
+        let middle = len / 2;
+        rng.random_range(middle.saturating_sub(spread)..=(middle + spread).min(len))
+    }
+
+    /// Drops the cards of two halves, listed top down, together from the bottom up
+    fn riffle(&self, mut left: Vec<usize>, mut right: Vec<usize>, rng: &mut impl Rng) -> Vec<usize> {
+        let mut dropped = Vec::with_capacity(left.len() + right.len());
+        let mut from_left = true;
+        while !left.is_empty() || !right.is_empty() {
+            if rng.random_bool(self.imperfection) {
+                // A clumsy drop comes from either half, in proportion to the cards it has left
+                let total = left.len() + right.len();
+                from_left = rng.random_range(0..total) < left.len();
+            }
+            let card = if (from_left && !left.is_empty()) || right.is_empty() { left.pop() } else { right.pop() };
+            dropped.extend(card);
+            from_left = !from_left;
+        }
+        dropped.reverse();
+        dropped
+    }
+
+    /// Pulls roughly equal packets off the top, each landing on the one before,
+    /// so the top packet ends up at the bottom
+    fn cut_into_packets(&self, stack: Vec<usize>, packets: u8, rng: &mut impl Rng) -> Vec<usize> {
+        if packets < 2 || stack.is_empty() {
+            return stack;
+        }
+        let packets = packets as usize;
+        let size = stack.len() / packets;
+        let spread = (self.imperfection * size as f64 / 4.0).round() as usize;
+        let mut bounds = vec![0];
+        for packet in 1..packets {
+            let target = packet * size;
+            let bound = rng.random_range(target.saturating_sub(spread)..=target + spread);
+            bounds.push(bound.clamp(*bounds.last().unwrap(), stack.len()));
+        }
+        bounds.push(stack.len());
+        bounds.windows(2).rev().flat_map(|bound| stack[bound[0]..bound[1]].iter().copied()).collect()
+    }
+
+    /// Checks that the shuffle can be carried out
+    ///
+    /// # Returns
+    /// `Ok(())` if the shuffle is valid, `Err` with the field at fault and a description if not
+    pub fn validate_with_key(&self) -> Result<(), (&'static str, String)> {
+        if self.grab_size == 0 {
+            return Err(("grab_size", "Each grab needs at least one card".to_string()));
+        }
+        if self.riffles == 0 {
+            return Err(("riffles", "Each grab must be riffled at least once".to_string()));
+        }
+        if !(0f64..=1f64).contains(&self.imperfection) {
+            return Err(("imperfection", "Imperfection must be between 0 and 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CasinoShuffle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the discards picked up by a `CasinoShuffle` ended up in the new shoe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShuffleTrace {
+    /// Position of each discard, in the order the cards were dealt, counted from
+    /// the next card to come out of the new shoe
+    pub discard_positions: Vec<usize>,
+}
+
+impl ShuffleTrace {
+    /// New shoe positions of a slug, given as the range of discards in dealing
+    /// order, e.g. `0..52` for the first deck's worth dealt
+    ///
+    /// # Returns
+    /// The positions in the order they will be dealt
+    pub fn slug(&self, dealt: Range<usize>) -> Vec<usize> {
+        let mut positions = self.discard_positions[dealt].to_vec();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// The stretch of the new shoe a slug has been spread across, from its first
+    /// card to its last; `None` for an empty slug
+    pub fn slug_span(&self, dealt: Range<usize>) -> Option<Range<usize>> {
+        let positions = self.slug(dealt);
+        Some(*positions.first()?..*positions.last()? + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+
+    fn sorted(cards: &[Card]) -> Vec<String> {
+        let mut names: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_shuffle_keeps_every_card_and_traces_discards() {
+        let mut shoe = Shoe::new(6);
+        shoe.shuffle();
+        let before = sorted(&shoe.cards);
+        for _ in 0..200 {
+            shoe.draw_card();
+        }
+        let discards = shoe.discarded.clone();
+
+        let trace = CasinoShuffle::new().shuffle(&mut shoe);
+        assert!(shoe.discarded.is_empty());
+        assert_eq!(sorted(&shoe.cards), before);
+        for (dealt, position) in trace.discard_positions.iter().enumerate() {
+            assert_eq!(shoe.cards[shoe.cards.len() - 1 - position], discards[dealt]);
+        }
+    }
+
+    #[test]
+    fn test_perfect_riffle_spreads_slug_over_twice_its_length() {
+        let procedure = CasinoShuffle {
+            plug: false,
+            riffles: 1,
+            box_packets: 0,
+            imperfection: 0f64,
+            ..CasinoShuffle::new()
+        };
+        let mut shoe = Shoe::new(1);
+        while shoe.draw_card().is_some() {}
+        let discards = shoe.discarded.clone();
+
+        // The first half dealt lies at the bottom of the tray and is riffled with the second
+        let trace = procedure.shuffle(&mut shoe);
+        assert_eq!(trace.slug(0..26), (0..52).step_by(2).collect::<Vec<usize>>());
+        assert_eq!(trace.slug_span(0..26), Some(0..51));
+        assert_eq!(shoe.draw_card(), Some(discards[25].clone()));
+        assert_eq!(shoe.draw_card(), Some(discards[51].clone()));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(CasinoShuffle::new().validate_with_key().is_ok());
+        let procedure = CasinoShuffle { imperfection: 1.5, ..CasinoShuffle::new() };
+        assert_eq!(procedure.validate_with_key().unwrap_err().0, "imperfection");
+        let procedure = CasinoShuffle { riffles: 0, ..CasinoShuffle::new() };
+        assert_eq!(procedure.validate_with_key().unwrap_err().0, "riffles");
+    }
+}
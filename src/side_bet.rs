@@ -0,0 +1,476 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::card::{Card, Rank};
+use crate::hand::Hand;
+use crate::money::Money;
+use crate::rules::Payout;
+
+/// Optional wagers placed alongside the main bet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter)]
+pub enum SideBetKind {
+    /// Pays when the player's first two cards are a pair
+    PerfectPairs,
+    /// Pays on a three-card poker hand made from the player's first two cards
+    /// and the dealer's upcard
+    TwentyOnePlusThree,
+    /// Pays when the dealer busts, more the more cards the bust takes
+    BusterBlackjack,
+}
+
+impl SideBetKind {
+    /// Returns true for side bets settled on the dealer's finished hand rather
+    /// than on the opening deal
+    ///
+    /// The dealer completes the hand for these bets even when every player hand
+    /// has already been settled.
+    pub fn settles_after_dealer(&self) -> bool {
+        matches!(self, SideBetKind::BusterBlackjack)
+    }
+}
+
+impl fmt::Display for SideBetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SideBetKind::PerfectPairs => "Perfect Pairs",
+            SideBetKind::TwentyOnePlusThree => "21+3",
+            SideBetKind::BusterBlackjack => "Buster Blackjack",
+        })
+    }
+}
+
+/// How closely the two cards of a pair match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(EnumIter)]
+pub enum PairKind {
+    /// Same rank, one red and one black card
+    Mixed,
+    /// Same rank and colour, different suits
+    Colored,
+    /// Same rank and suit
+    Perfect,
+}
+
+impl PairKind {
+    /// Classifies two cards as a pair
+    ///
+    /// # Returns
+    /// `None` if the ranks differ
+    pub fn of(first: &Card, second: &Card) -> Option<PairKind> {
+        if first.rank != second.rank {
+            None
+        } else if first.suit == second.suit {
+            Some(PairKind::Perfect)
+        } else if first.suit.is_red() == second.suit.is_red() {
+            Some(PairKind::Colored)
+        } else {
+            Some(PairKind::Mixed)
+        }
+    }
+}
+
+impl fmt::Display for PairKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PairKind::Mixed => "Mixed pair",
+            PairKind::Colored => "Colored pair",
+            PairKind::Perfect => "Perfect pair",
+        })
+    }
+}
+
+/// Three-card poker hands that win the 21+3 side bet, from lowest to highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(EnumIter)]
+pub enum PokerHand {
+    Flush,
+    /// Three ranks in a row; aces play high (Q-K-A) or low (A-2-3)
+    Straight,
+    ThreeOfAKind,
+    StraightFlush,
+    /// Three cards of the same rank and suit
+    SuitedTrips,
+}
+
+impl PokerHand {
+    /// The best poker hand made by three cards
+    ///
+    /// # Returns
+    /// `None` if the cards make nothing better than a pair
+    pub fn of(cards: [&Card; 3]) -> Option<PokerHand> {
+        let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+        let mut ranks = cards.map(|card| poker_rank(&card.rank));
+        ranks.sort_unstable();
+        let trips = ranks[0] == ranks[2];
+        let straight = (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1) || ranks == [2, 3, 14];
+        match (flush, straight, trips) {
+            (true, _, true) => Some(PokerHand::SuitedTrips),
+            (true, true, _) => Some(PokerHand::StraightFlush),
+            (_, _, true) => Some(PokerHand::ThreeOfAKind),
+            (_, true, _) => Some(PokerHand::Straight),
+            (true, _, _) => Some(PokerHand::Flush),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PokerHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PokerHand::Flush => "Flush",
+            PokerHand::Straight => "Straight",
+            PokerHand::ThreeOfAKind => "Three of a kind",
+            PokerHand::StraightFlush => "Straight flush",
+            PokerHand::SuitedTrips => "Suited trips",
+        })
+    }
+}
+
+/// Poker value of a rank, from 2 for a two up to 14 for an ace
+fn poker_rank(rank: &Rank) -> u8 {
+    Rank::iter().position(|other| other == *rank).unwrap_or_default() as u8 + 2
+}
+
+/// Perfect Pairs pay table
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PerfectPairsPays {
+    pub mixed: Payout,
+    pub colored: Payout,
+    pub perfect: Payout,
+}
+
+impl PerfectPairsPays {
+    /// The most common pay table: 6:1, 12:1 and 25:1
+    pub fn new() -> Self {
+        Self {
+            mixed: Payout { numerator: 6, denominator: 1 },
+            colored: Payout { numerator: 12, denominator: 1 },
+            perfect: Payout { numerator: 25, denominator: 1 },
+        }
+    }
+
+    /// What a pair of this kind pays
+    pub fn pays(&self, pair: PairKind) -> Payout {
+        match pair {
+            PairKind::Mixed => self.mixed,
+            PairKind::Colored => self.colored,
+            PairKind::Perfect => self.perfect,
+        }
+    }
+}
+
+impl Default for PerfectPairsPays {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 21+3 pay table
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TwentyOnePlusThreePays {
+    pub flush: Payout,
+    pub straight: Payout,
+    pub three_of_a_kind: Payout,
+    pub straight_flush: Payout,
+    pub suited_trips: Payout,
+}
+
+impl TwentyOnePlusThreePays {
+    /// The most common multi-deck pay table: 5:1, 10:1, 30:1, 40:1 and 100:1
+    pub fn new() -> Self {
+        Self {
+            flush: Payout { numerator: 5, denominator: 1 },
+            straight: Payout { numerator: 10, denominator: 1 },
+            three_of_a_kind: Payout { numerator: 30, denominator: 1 },
+            straight_flush: Payout { numerator: 40, denominator: 1 },
+            suited_trips: Payout { numerator: 100, denominator: 1 },
+        }
+    }
+
+    /// What a poker hand pays
+    pub fn pays(&self, hand: PokerHand) -> Payout {
+        match hand {
+            PokerHand::Flush => self.flush,
+            PokerHand::Straight => self.straight,
+            PokerHand::ThreeOfAKind => self.three_of_a_kind,
+            PokerHand::StraightFlush => self.straight_flush,
+            PokerHand::SuitedTrips => self.suited_trips,
+        }
+    }
+}
+
+impl Default for TwentyOnePlusThreePays {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Buster Blackjack pay table, by the number of cards in the dealer's busted hand
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BusterBlackjackPays {
+    pub three_cards: Payout,
+    pub four_cards: Payout,
+    pub five_cards: Payout,
+    pub six_cards: Payout,
+    pub seven_cards: Payout,
+    pub eight_or_more_cards: Payout,
+}
+
+impl BusterBlackjackPays {
+    /// A common six-deck pay table: 1:1, 2:1, 9:1, 50:1, 100:1 and 250:1
+    pub fn new() -> Self {
+        Self {
+            three_cards: Payout::EVEN_MONEY,
+            four_cards: Payout { numerator: 2, denominator: 1 },
+            five_cards: Payout { numerator: 9, denominator: 1 },
+            six_cards: Payout { numerator: 50, denominator: 1 },
+            seven_cards: Payout { numerator: 100, denominator: 1 },
+            eight_or_more_cards: Payout { numerator: 250, denominator: 1 },
+        }
+    }
+
+    /// What a dealer bust with `card_count` cards pays
+    pub fn pays(&self, card_count: usize) -> Payout {
+        match card_count {
+            0..=3 => self.three_cards,
+            4 => self.four_cards,
+            5 => self.five_cards,
+            6 => self.six_cards,
+            7 => self.seven_cards,
+            _ => self.eight_or_more_cards,
+        }
+    }
+
+    fn all(&self) -> [Payout; 6] {
+        [self.three_cards, self.four_cards, self.five_cards, self.six_cards, self.seven_cards, self.eight_or_more_cards]
+    }
+}
+
+impl Default for BusterBlackjackPays {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Side bets offered at a table and their pay tables
+///
+/// No side bets are offered by default. In a config file an empty
+/// `[side_bets.perfect_pairs]` table offers Perfect Pairs with the usual pays.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SideBetRules {
+    /// Largest wager accepted on any one side bet
+    pub max_bet: Money,
+    /// Perfect Pairs pay table, or `None` if the table does not offer it
+    pub perfect_pairs: Option<PerfectPairsPays>,
+    /// 21+3 pay table, or `None` if the table does not offer it
+    pub twenty_one_plus_three: Option<TwentyOnePlusThreePays>,
+    /// Buster Blackjack pay table, or `None` if the table does not offer it
+    pub buster_blackjack: Option<BusterBlackjackPays>,
+}
+
+impl SideBetRules {
+    /// No side bets, with a $100 limit once any are offered
+    pub fn new() -> Self {
+        Self {
+            max_bet: Money::from_dollars(100),
+            perfect_pairs: None,
+            twenty_one_plus_three: None,
+            buster_blackjack: None,
+        }
+    }
+
+    /// Returns true if the table takes wagers on `kind`
+    pub fn offers(&self, kind: SideBetKind) -> bool {
+        match kind {
+            SideBetKind::PerfectPairs => self.perfect_pairs.is_some(),
+            SideBetKind::TwentyOnePlusThree => self.twenty_one_plus_three.is_some(),
+            SideBetKind::BusterBlackjack => self.buster_blackjack.is_some(),
+        }
+    }
+
+    /// Works out whether a side bet won
+    ///
+    /// # Arguments
+    /// * `kind` - The side bet to settle
+    /// * `player_cards` - The player's first two cards
+    /// * `dealer_hand` - The dealer's upcard after the deal, or the finished hand
+    ///   for bets that `settle_after_dealer`
+    ///
+    /// # Returns
+    /// The name of the winning hand and what it pays, or `None` if the wager
+    /// loses or the table does not offer it
+    pub fn evaluate(&self, kind: SideBetKind, player_cards: &[Card], dealer_hand: &Hand) -> Option<(String, Payout)> {
+        match kind {
+            SideBetKind::PerfectPairs => {
+                let pays = self.perfect_pairs.as_ref()?;
+                let pair = PairKind::of(player_cards.first()?, player_cards.get(1)?)?;
+                Some((pair.to_string(), pays.pays(pair)))
+            }
+            SideBetKind::TwentyOnePlusThree => {
+                let pays = self.twenty_one_plus_three.as_ref()?;
+                let hand = PokerHand::of([player_cards.first()?, player_cards.get(1)?, dealer_hand.cards.first()?])?;
+                Some((hand.to_string(), pays.pays(hand)))
+            }
+            SideBetKind::BusterBlackjack => {
+                let pays = self.buster_blackjack.as_ref()?;
+                if !dealer_hand.is_busted() {
+                    return None;
+                }
+                let card_count = dealer_hand.cards.len();
+                Some((format!("Dealer busts with {} cards", card_count), pays.pays(card_count)))
+            }
+        }
+    }
+
+    /// Checks that the limit and every pay table make sense
+    ///
+    /// # Returns
+    /// `Ok(())` if valid, `Err` naming the setting at fault and describing the problem if not
+    pub fn validate_with_key(&self) -> Result<(), (&'static str, String)> {
+        if !self.max_bet.is_positive() {
+            return Err(("max_bet", "Side bet maximum must be greater than zero".to_string()));
+        }
+        if let Some(pays) = &self.perfect_pairs {
+            if [pays.mixed, pays.colored, pays.perfect].iter().any(|pay| pay.numerator <= 0 || pay.denominator <= 0) {
+                return Err(("perfect_pairs", "Perfect Pairs pays must be positive ratios".to_string()));
+            }
+        }
+        if let Some(pays) = &self.twenty_one_plus_three {
+            if PokerHand::iter().map(|hand| pays.pays(hand)).any(|pay| pay.numerator <= 0 || pay.denominator <= 0) {
+                return Err(("twenty_one_plus_three", "21+3 pays must be positive ratios".to_string()));
+            }
+        }
+        if let Some(pays) = &self.buster_blackjack {
+            if pays.all().iter().any(|pay| pay.numerator <= 0 || pay.denominator <= 0) {
+                return Err(("buster_blackjack", "Buster Blackjack pays must be positive ratios".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for SideBetRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A side wager and, once settled, how it turned out
+#[derive(Debug, Clone, PartialEq)]
+pub struct SideBet {
+    pub kind: SideBetKind,
+    pub amount: Money,
+    /// `None` until the side bet is settled
+    pub outcome: Option<SideBetOutcome>,
+}
+
+impl SideBet {
+    pub fn new(kind: SideBetKind, amount: Money) -> Self {
+        Self { kind, amount, outcome: None }
+    }
+}
+
+impl fmt::Display for SideBet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.amount)?;
+        if let Some(outcome) = &self.outcome {
+            write!(f, ": {}", outcome)?;
+        }
+        Ok(())
+    }
+}
+
+/// How a settled side bet turned out
+#[derive(Debug, Clone, PartialEq)]
+pub enum SideBetOutcome {
+    /// The wager hit `hand` and paid `winnings` on top of the returned stake
+    Win { hand: String, pays: Payout, winnings: Money },
+    Loss,
+}
+
+impl fmt::Display for SideBetOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SideBetOutcome::Win { hand, pays, winnings } => write!(f, "{} pays {} (+{})", hand, pays, winnings),
+            SideBetOutcome::Loss => f.write_str("LOSS"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn pair(cards: &str) -> Option<PairKind> {
+        let cards = parse_cards(cards).unwrap();
+        PairKind::of(&cards[0], &cards[1])
+    }
+
+    #[test]
+    fn test_pair_kinds() {
+        assert_eq!(pair("8s 8s"), Some(PairKind::Perfect));
+        assert_eq!(pair("8s 8c"), Some(PairKind::Colored));
+        assert_eq!(pair("Qh Qd"), Some(PairKind::Colored));
+        assert_eq!(pair("8s 8h"), Some(PairKind::Mixed));
+        // Ten-valued cards of different ranks are not a pair
+        assert_eq!(pair("Ks Qs"), None);
+    }
+
+    #[test]
+    fn test_poker_hands() {
+        let hand = |cards: &str| {
+            let cards = parse_cards(cards).unwrap();
+            PokerHand::of([&cards[0], &cards[1], &cards[2]])
+        };
+        assert_eq!(hand("7h 7h 7h"), Some(PokerHand::SuitedTrips));
+        assert_eq!(hand("7h 7s 7d"), Some(PokerHand::ThreeOfAKind));
+        assert_eq!(hand("9c Tc 8c"), Some(PokerHand::StraightFlush));
+        assert_eq!(hand("Qd As Kh"), Some(PokerHand::Straight));
+        assert_eq!(hand("3d As 2h"), Some(PokerHand::Straight));
+        assert_eq!(hand("Kd As 2h"), None);
+        assert_eq!(hand("2s 9s Js"), Some(PokerHand::Flush));
+        assert_eq!(hand("8s 8d Js"), None);
+    }
+
+    #[test]
+    fn test_perfect_pairs_pay_table() {
+        let upcard: Hand = "9c".parse().unwrap();
+        let mut rules = SideBetRules::new();
+        let player = parse_cards("5d 5h").unwrap();
+        assert_eq!(rules.evaluate(SideBetKind::PerfectPairs, &player, &upcard), None);
+
+        rules.perfect_pairs = Some(PerfectPairsPays {
+            colored: Payout { numerator: 10, denominator: 1 },
+            ..PerfectPairsPays::new()
+        });
+        assert_eq!(
+            rules.evaluate(SideBetKind::PerfectPairs, &player, &upcard),
+            Some(("Colored pair".to_string(), Payout { numerator: 10, denominator: 1 })),
+        );
+        let player = parse_cards("5d 6d").unwrap();
+        assert_eq!(rules.evaluate(SideBetKind::PerfectPairs, &player, &upcard), None);
+    }
+
+    #[test]
+    fn test_buster_blackjack_pays_by_bust_card_count() {
+        let rules = SideBetRules { buster_blackjack: Some(BusterBlackjackPays::new()), ..SideBetRules::new() };
+        let player = parse_cards("Ts 8d").unwrap();
+        let evaluate = |dealer: &str| rules.evaluate(SideBetKind::BusterBlackjack, &player, &dealer.parse().unwrap());
+
+        assert_eq!(evaluate("Tc 6h 9d"), Some(("Dealer busts with 3 cards".to_string(), Payout::EVEN_MONEY)));
+        assert_eq!(evaluate("2c 3h 2d 4s 5c Kd").unwrap().1, Payout { numerator: 50, denominator: 1 });
+        assert_eq!(evaluate("2c 2h 2d 2s Ac Ad 4s Th").unwrap().1, Payout { numerator: 250, denominator: 1 });
+        assert_eq!(evaluate("Tc 6h 5d"), None);
+    }
+}
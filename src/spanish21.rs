@@ -0,0 +1,85 @@
+use crate::card::{Rank, Suit};
+use crate::hand::Hand;
+use crate::rules::Payout;
+
+const TWO_TO_ONE: Payout = Payout { numerator: 2, denominator: 1 };
+const THREE_TO_ONE: Payout = Payout { numerator: 3, denominator: 1 };
+
+/// What a Spanish 21 hand of 21 pays in place of even money
+///
+/// 6-7-8 and 7-7-7 pay 3:2 in mixed suits, 2:1 when suited and 3:1 in spades.
+/// Other 21s pay 3:2 with five cards, 2:1 with six and 3:1 with seven or more.
+/// Doubled hands and naturals earn no bonus.
+///
+/// # Returns
+/// `None` if the hand earns no bonus
+///
+/// # Examples
+///
+/// ```
+/// use blackjack_engine::hand::Hand;
+/// use blackjack_engine::rules::Payout;
+/// use blackjack_engine::spanish21::twenty_one_bonus;
+///
+/// let hand: Hand = "7s 6s 8s".parse().unwrap();
+/// assert_eq!(twenty_one_bonus(&hand), Some(Payout { numerator: 3, denominator: 1 }));
+/// ```
+pub fn twenty_one_bonus(hand: &Hand) -> Option<Payout> {
+    if hand.doubled || hand.best_value() != 21 {
+        return None;
+    }
+
+    let card_count = hand.cards.len();
+    if card_count == 3 {
+        let mut ranks: Vec<&Rank> = hand.cards.iter().map(|card| &card.rank).collect();
+        ranks.sort_by_key(|rank| rank.value()[0]);
+        let six_seven_eight = ranks == [&Rank::Six, &Rank::Seven, &Rank::Eight];
+        let seven_seven_seven = ranks.iter().all(|rank| **rank == Rank::Seven);
+        if six_seven_eight || seven_seven_seven {
+            let suit = &hand.cards[0].suit;
+            let suited = hand.cards.iter().all(|card| card.suit == *suit);
+            return Some(match (suited, suit) {
+                (true, Suit::Spades) => THREE_TO_ONE,
+                (true, _) => TWO_TO_ONE,
+                _ => Payout::THREE_TO_TWO,
+            });
+        }
+    }
+
+    match card_count {
+        0..=4 => None,
+        5 => Some(Payout::THREE_TO_TWO),
+        6 => Some(TWO_TO_ONE),
+        _ => Some(THREE_TO_ONE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bonus(cards: &str) -> Option<Payout> {
+        twenty_one_bonus(&cards.parse().unwrap())
+    }
+
+    #[test]
+    fn test_six_seven_eight_and_seven_seven_seven() {
+        assert_eq!(bonus("8h 6c 7d"), Some(Payout::THREE_TO_TWO));
+        assert_eq!(bonus("7d 7d 7d"), Some(TWO_TO_ONE));
+        assert_eq!(bonus("7s 7s 7s"), Some(THREE_TO_ONE));
+        assert_eq!(bonus("9s 5s 7s"), None);
+    }
+
+    #[test]
+    fn test_multi_card_twenty_ones() {
+        assert_eq!(bonus("2s 3d 4c 5h 7s"), Some(Payout::THREE_TO_TWO));
+        assert_eq!(bonus("As 2d 3c 4h 5s 6c"), Some(TWO_TO_ONE));
+        assert_eq!(bonus("As Ad 2c 3h 4s 5c 5d"), Some(THREE_TO_ONE));
+        assert_eq!(bonus("2s 3d 4c 5h 6s"), None);
+        assert_eq!(bonus("As Kd"), None);
+
+        let mut doubled: Hand = "2s 3d 4c 5h 7s".parse().unwrap();
+        doubled.doubled = true;
+        assert_eq!(twenty_one_bonus(&doubled), None);
+    }
+}
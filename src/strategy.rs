@@ -0,0 +1,161 @@
+use crate::card::{Card, Rank};
+use crate::game::GameAction;
+use crate::hand::Hand;
+
+/// Basic strategy for a multi-deck game where the dealer stands on soft 17 and
+/// doubling after a split is allowed
+///
+/// # Arguments
+/// * `hand` - The player's hand
+/// * `dealer_upcard` - The dealer's face-up card
+/// * `can_double` - Whether doubling is allowed (and affordable) on this hand
+/// * `can_split` - Whether splitting is allowed (and affordable) on this hand
+/// * `can_surrender` - Whether late surrender is offered on this hand
+pub fn basic_strategy(
+    hand: &Hand,
+    dealer_upcard: &Card,
+    can_double: bool,
+    can_split: bool,
+    can_surrender: bool,
+) -> GameAction {
+    let upcard = upcard_value(dealer_upcard);
+    let can_double = can_double && hand.cards.len() == 2;
+
+    if can_surrender && hand.cards.len() == 2 && should_surrender(hand, upcard) {
+        return GameAction::Surrender;
+    }
+
+    if can_split && hand.can_split() {
+        if let Some(action) = pair_decision(&hand.cards[0].rank, upcard) {
+            return action;
+        }
+    }
+
+    let total = hand.best_value();
+    if hand.is_soft() {
+        soft_decision(total, upcard, can_double)
+    } else {
+        hard_decision(total, upcard, can_double)
+    }
+}
+
+/// Dealer upcard value with aces counted as 11
+fn upcard_value(card: &Card) -> u32 {
+    match card.rank {
+        Rank::Ace => 11,
+        _ => card.rank.value()[0] as u32,
+    }
+}
+
+/// Hard 16 against 9, 10 or ace and hard 15 against 10; a pair of eights is split instead
+fn should_surrender(hand: &Hand, upcard: u32) -> bool {
+    if hand.is_soft() || (hand.cards[0].rank == Rank::Eight && hand.can_split()) {
+        return false;
+    }
+    match hand.best_value() {
+        16 => upcard >= 9,
+        15 => upcard == 10,
+        _ => false,
+    }
+}
+
+/// Returns `Some(Split)` when the pair should be split, `None` to play it as a total
+fn pair_decision(rank: &Rank, upcard: u32) -> Option<GameAction> {
+    let split = match rank {
+        Rank::Ace | Rank::Eight => true,
+        Rank::Nine => matches!(upcard, 2..=6 | 8 | 9),
+        Rank::Seven | Rank::Three | Rank::Two => (2..=7).contains(&upcard),
+        Rank::Six => (2..=6).contains(&upcard),
+        Rank::Four => (5..=6).contains(&upcard),
+        _ => false,
+    };
+    split.then_some(GameAction::Split)
+}
+
+fn soft_decision(total: u32, upcard: u32, can_double: bool) -> GameAction {
+    let double_or = |fallback: GameAction| if can_double { GameAction::Double } else { fallback };
+    match total {
+        19.. => GameAction::Stand,
+        18 => match upcard {
+            3..=6 => double_or(GameAction::Stand),
+            2 | 7 | 8 => GameAction::Stand,
+            _ => GameAction::Hit,
+        },
+        17 if (3..=6).contains(&upcard) => double_or(GameAction::Hit),
+        15 | 16 if (4..=6).contains(&upcard) => double_or(GameAction::Hit),
+        13 | 14 if (5..=6).contains(&upcard) => double_or(GameAction::Hit),
+        _ => GameAction::Hit,
+    }
+}
+
+fn hard_decision(total: u32, upcard: u32, can_double: bool) -> GameAction {
+    let double_or = |fallback: GameAction| if can_double { GameAction::Double } else { fallback };
+    match total {
+        17.. => GameAction::Stand,
+        13..=16 if upcard <= 6 => GameAction::Stand,
+        12 if (4..=6).contains(&upcard) => GameAction::Stand,
+        11 if upcard <= 10 => double_or(GameAction::Hit),
+        10 if upcard <= 9 => double_or(GameAction::Hit),
+        9 if (3..=6).contains(&upcard) => double_or(GameAction::Hit),
+        _ => GameAction::Hit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decide(hand: &str, upcard: &str) -> GameAction {
+        let hand: Hand = hand.parse().unwrap();
+        basic_strategy(&hand, &upcard.parse().unwrap(), true, true, false)
+    }
+
+    #[test]
+    fn test_hard_totals() {
+        assert_eq!(decide("Ts 6d", "Tc"), GameAction::Hit);
+        assert_eq!(decide("Ts 6d", "6c"), GameAction::Stand);
+        assert_eq!(decide("Ts 2d", "3c"), GameAction::Hit);
+        assert_eq!(decide("6s 5d", "Tc"), GameAction::Double);
+        assert_eq!(decide("6s 5d", "Ac"), GameAction::Hit);
+        assert_eq!(decide("5s 4d", "2c"), GameAction::Hit);
+        assert_eq!(decide("Ts 7d", "Ac"), GameAction::Stand);
+    }
+
+    #[test]
+    fn test_soft_totals() {
+        assert_eq!(decide("As 7d", "4c"), GameAction::Double);
+        assert_eq!(decide("As 7d", "8c"), GameAction::Stand);
+        assert_eq!(decide("As 7d", "9c"), GameAction::Hit);
+        assert_eq!(decide("As 2d", "4c"), GameAction::Hit);
+        assert_eq!(decide("As 2d", "5c"), GameAction::Double);
+    }
+
+    #[test]
+    fn test_pairs() {
+        assert_eq!(decide("8s 8d", "Tc"), GameAction::Split);
+        assert_eq!(decide("As Ad", "Ac"), GameAction::Split);
+        assert_eq!(decide("Ts Td", "6c"), GameAction::Stand);
+        assert_eq!(decide("5s 5d", "6c"), GameAction::Double);
+        assert_eq!(decide("9s 9d", "7c"), GameAction::Stand);
+    }
+
+    #[test]
+    fn test_no_double_after_third_card() {
+        assert_eq!(decide("As 2d 4c", "5c"), GameAction::Hit);
+        assert_eq!(decide("As 3d 4c", "5c"), GameAction::Stand);
+        assert_eq!(decide("3s 3d 5c", "Tc"), GameAction::Hit);
+    }
+
+    #[test]
+    fn test_late_surrender() {
+        let surrender = |hand: &str, upcard: &str| {
+            let hand: Hand = hand.parse().unwrap();
+            basic_strategy(&hand, &upcard.parse().unwrap(), true, true, true)
+        };
+        assert_eq!(surrender("Ts 6d", "Ac"), GameAction::Surrender);
+        assert_eq!(surrender("Ts 5d", "Kc"), GameAction::Surrender);
+        assert_eq!(surrender("Ts 5d", "9c"), GameAction::Hit);
+        assert_eq!(surrender("8s 8d", "Tc"), GameAction::Split);
+        assert_eq!(decide("Ts 6d", "Ac"), GameAction::Hit);
+    }
+}
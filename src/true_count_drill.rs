@@ -0,0 +1,372 @@
+use rand::Rng;
+use crate::counting::true_count;
+use crate::shoe::Shoe;
+
+/// Precision used when estimating the decks in the discard tray
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeckRounding {
+    FullDeck,
+    #[default]
+    HalfDeck,
+    QuarterDeck,
+}
+
+impl DeckRounding {
+    /// Size of one estimation step in decks
+    pub fn step(&self) -> f64 {
+        match self {
+            DeckRounding::FullDeck => 1.0,
+            DeckRounding::HalfDeck => 0.5,
+            DeckRounding::QuarterDeck => 0.25,
+        }
+    }
+
+    /// Rounds a number of decks to the nearest step
+    pub fn round(&self, decks: f64) -> f64 {
+        (decks / self.step()).round() * self.step()
+    }
+}
+
+/// How the discard tray is described in a true count prompt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiscardAmount {
+    /// Exact number of cards discarded
+    Cards(usize),
+    /// Decks discarded, as a player would read them off the tray
+    Decks(f64),
+}
+
+impl DiscardAmount {
+    pub fn decks(&self) -> f64 {
+        match self {
+            DiscardAmount::Cards(cards) => *cards as f64 / 52f64,
+            DiscardAmount::Decks(decks) => *decks,
+        }
+    }
+}
+
+/// A running count and discard tray to convert into a true count
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrueCountPrompt {
+    pub running_count: i32,
+    pub discard: DiscardAmount,
+    /// Decks in the shoe before any were dealt
+    pub deck_count: usize,
+}
+
+impl TrueCountPrompt {
+    /// Decks remaining once the discard is rounded to the given precision
+    ///
+    /// Never less than one rounding step, since some cards are always left
+    /// behind the cut card.
+    pub fn decks_remaining(&self, rounding: DeckRounding) -> f64 {
+        let discarded = rounding.round(self.discard.decks());
+        (self.deck_count as f64 - discarded).max(rounding.step())
+    }
+
+    /// The true count a player using `rounding` should arrive at, truncated towards zero
+    pub fn expected_true_count(&self, rounding: DeckRounding) -> i32 {
+        true_count(self.running_count, self.decks_remaining(rounding)).trunc() as i32
+    }
+}
+
+/// How the discard is shown in true count prompts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscardFormat {
+    Cards,
+    Decks,
+}
+
+/// Configuration for the true count conversion drill
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrueCountDrillSettings {
+    pub deck_count: usize,
+    pub rounding: DeckRounding,
+    pub discard_format: DiscardFormat,
+    /// Largest running count (positive or negative) a prompt will show
+    pub max_running_count: i32,
+}
+
+impl TrueCountDrillSettings {
+    /// Six-deck drill with half-deck rounding, discards shown in decks
+    pub fn new() -> Self {
+        Self {
+            deck_count: 6,
+            rounding: DeckRounding::HalfDeck,
+            discard_format: DiscardFormat::Decks,
+            max_running_count: 15,
+        }
+    }
+}
+
+impl Default for TrueCountDrillSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Outcome of one true count conversion
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrueCountResult {
+    pub prompt: TrueCountPrompt,
+    pub expected: i32,
+    pub answer: i32,
+}
+
+impl TrueCountResult {
+    pub fn is_correct(&self) -> bool {
+        self.expected == self.answer
+    }
+}
+
+/// Presents running counts and discard trays and grades the true count answers
+pub struct TrueCountDrill {
+    pub settings: TrueCountDrillSettings,
+    results: Vec<TrueCountResult>,
+}
+
+impl TrueCountDrill {
+    pub fn new(settings: TrueCountDrillSettings) -> Self {
+        Self {
+            settings,
+            results: Vec::new(),
+        }
+    }
+
+    /// Generates a random prompt, leaving at least half a deck undealt
+    pub fn next_prompt(&self) -> TrueCountPrompt {
+        let mut rng = rand::rng();
+        let max_discard = (self.settings.deck_count * 52).saturating_sub(26).max(1);
+        let cards = rng.random_range(0..max_discard);
+        let discard = match self.settings.discard_format {
+            DiscardFormat::Cards => DiscardAmount::Cards(cards),
+            DiscardFormat::Decks => DiscardAmount::Decks(self.settings.rounding.round(cards as f64 / 52f64)),
+        };
+        let max_count = self.settings.max_running_count.abs();
+        TrueCountPrompt {
+            running_count: rng.random_range(-max_count..=max_count),
+            discard,
+            deck_count: self.settings.deck_count,
+        }
+    }
+
+    /// Grades a true count answer for `prompt`
+    pub fn grade(&mut self, prompt: TrueCountPrompt, answer: i32) -> TrueCountResult {
+        let result = TrueCountResult {
+            expected: prompt.expected_true_count(self.settings.rounding),
+            prompt,
+            answer,
+        };
+        self.results.push(result.clone());
+        result
+    }
+
+    pub fn results(&self) -> &[TrueCountResult] {
+        &self.results
+    }
+
+    /// Fraction of answers that were correct, 0.0 before any answers
+    pub fn accuracy(&self) -> f64 {
+        accuracy(self.results.iter().map(TrueCountResult::is_correct))
+    }
+}
+
+/// Outcome of one discard tray estimate
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscardEstimateResult {
+    pub cards_discarded: usize,
+    /// Exact decks in the tray
+    pub actual_decks: f64,
+    /// Actual decks rounded to the drill's precision
+    pub expected: f64,
+    pub answer: f64,
+}
+
+impl DiscardEstimateResult {
+    pub fn is_correct(&self) -> bool {
+        (self.answer - self.expected).abs() < 1e-9
+    }
+
+    /// How far the estimate was from the real tray, in decks
+    pub fn error(&self) -> f64 {
+        self.answer - self.actual_decks
+    }
+}
+
+/// Deals cards into the discard tray and asks how many decks it holds
+///
+/// The tray is the shoe's `discarded` pile, so a UI can draw it at its real height.
+pub struct DiscardEstimateDrill {
+    pub shoe: Shoe,
+    pub rounding: DeckRounding,
+    results: Vec<DiscardEstimateResult>,
+}
+
+impl DiscardEstimateDrill {
+    /// Creates a drill with a freshly shuffled shoe of `deck_count` decks
+    pub fn new(deck_count: usize, rounding: DeckRounding) -> Self {
+        let mut shoe = Shoe::new(deck_count);
+        shoe.shuffle();
+        Self::with_shoe(shoe, rounding)
+    }
+
+    pub fn with_shoe(shoe: Shoe, rounding: DeckRounding) -> Self {
+        Self {
+            shoe,
+            rounding,
+            results: Vec::new(),
+        }
+    }
+
+    /// Refills the shoe and deals a random amount into the discard tray
+    ///
+    /// # Returns
+    /// The number of cards now in the tray
+    pub fn next_tray(&mut self) -> usize {
+        let deck_count = self.shoe.number_of_decks();
+        let mut shoe = Shoe::new(deck_count);
+        shoe.shuffle();
+        self.shoe = shoe;
+
+        let total = deck_count * 52;
+        let cards = rand::rng().random_range(0..total.saturating_sub(26).max(1));
+        for _ in 0..cards {
+            self.shoe.draw_card();
+        }
+        cards
+    }
+
+    /// Grades an estimate of the decks currently in the discard tray
+    pub fn grade(&mut self, answer: f64) -> DiscardEstimateResult {
+        let cards_discarded = self.shoe.discarded.len();
+        let actual_decks = cards_discarded as f64 / 52f64;
+        let result = DiscardEstimateResult {
+            cards_discarded,
+            actual_decks,
+            expected: self.rounding.round(actual_decks),
+            answer,
+        };
+        self.results.push(result.clone());
+        result
+    }
+
+    pub fn results(&self) -> &[DiscardEstimateResult] {
+        &self.results
+    }
+
+    /// Fraction of estimates that were correct, 0.0 before any answers
+    pub fn accuracy(&self) -> f64 {
+        accuracy(self.results.iter().map(DiscardEstimateResult::is_correct))
+    }
+
+    /// Average absolute estimation error in decks
+    pub fn average_error(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0f64;
+        }
+        self.results.iter().map(|result| result.error().abs()).sum::<f64>() / self.results.len() as f64
+    }
+}
+
+fn accuracy(outcomes: impl Iterator<Item = bool>) -> f64 {
+    let (correct, total) = outcomes.fold((0, 0), |(correct, total), is_correct| {
+        (correct + is_correct as usize, total + 1)
+    });
+    if total == 0 {
+        return 0f64;
+    }
+    correct as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deck_rounding() {
+        assert_eq!(DeckRounding::HalfDeck.round(2.3), 2.5);
+        assert_eq!(DeckRounding::HalfDeck.round(2.2), 2.0);
+        assert_eq!(DeckRounding::QuarterDeck.round(2.3), 2.25);
+        assert_eq!(DeckRounding::FullDeck.round(2.6), 3.0);
+    }
+
+    #[test]
+    fn test_expected_true_count() {
+        // 6 decks, 2.5 discarded leaves 3.5: 9 / 3.5 = 2.57, or 9 / 3 with full decks
+        let prompt = TrueCountPrompt {
+            running_count: 9,
+            discard: DiscardAmount::Decks(2.5),
+            deck_count: 6,
+        };
+        assert_eq!(prompt.expected_true_count(DeckRounding::HalfDeck), 2);
+        assert_eq!(prompt.expected_true_count(DeckRounding::FullDeck), 3);
+
+        // 130 cards is exactly 2.5 decks; full-deck rounding makes it 3 discarded
+        let prompt = TrueCountPrompt {
+            running_count: -7,
+            discard: DiscardAmount::Cards(130),
+            deck_count: 6,
+        };
+        assert_eq!(prompt.decks_remaining(DeckRounding::FullDeck), 3.0);
+        assert_eq!(prompt.expected_true_count(DeckRounding::FullDeck), -2);
+        assert_eq!(prompt.expected_true_count(DeckRounding::HalfDeck), -2);
+    }
+
+    #[test]
+    fn test_decks_remaining_never_below_one_step() {
+        let prompt = TrueCountPrompt {
+            running_count: 4,
+            discard: DiscardAmount::Cards(310),
+            deck_count: 6,
+        };
+        assert_eq!(prompt.decks_remaining(DeckRounding::QuarterDeck), 0.25);
+        assert_eq!(prompt.expected_true_count(DeckRounding::QuarterDeck), 16);
+    }
+
+    #[test]
+    fn test_grade_true_count() {
+        let mut drill = TrueCountDrill::new(TrueCountDrillSettings::new());
+        let prompt = TrueCountPrompt {
+            running_count: 12,
+            discard: DiscardAmount::Decks(2.0),
+            deck_count: 6,
+        };
+        assert!(drill.grade(prompt.clone(), 3).is_correct());
+        assert!(!drill.grade(prompt, 2).is_correct());
+        assert_eq!(drill.accuracy(), 0.5);
+    }
+
+    #[test]
+    fn test_random_prompts_stay_in_range() {
+        let mut settings = TrueCountDrillSettings::new();
+        settings.discard_format = DiscardFormat::Cards;
+        let drill = TrueCountDrill::new(settings);
+        for _ in 0..100 {
+            let prompt = drill.next_prompt();
+            assert!(prompt.running_count.abs() <= 15);
+            assert!(matches!(prompt.discard, DiscardAmount::Cards(cards) if cards < 6 * 52 - 26));
+        }
+    }
+
+    #[test]
+    fn test_discard_estimate_uses_shoe_discards() {
+        let mut drill = DiscardEstimateDrill::new(6, DeckRounding::HalfDeck);
+        for _ in 0..130 {
+            drill.shoe.draw_card();
+        }
+
+        let result = drill.grade(2.5);
+        assert_eq!(result.cards_discarded, 130);
+        assert!(result.is_correct());
+        assert!(!drill.grade(2.0).is_correct());
+        assert_eq!(drill.average_error(), 0.25);
+    }
+
+    #[test]
+    fn test_next_tray_refills_shoe() {
+        let mut drill = DiscardEstimateDrill::new(2, DeckRounding::QuarterDeck);
+        let cards = drill.next_tray();
+        assert_eq!(drill.shoe.discarded.len(), cards);
+        assert_eq!(drill.shoe.cards.len() + cards, 104);
+    }
+}